@@ -0,0 +1,84 @@
+use specs::prelude::*;
+use rltk::{DijkstraMap, BaseMap, Point};
+use std::collections::VecDeque;
+use super::{Map, RunState};
+
+// How far from the player the flow maps are filled in
+const MAX_DEPTH : f32 = 200.0;
+
+// Shared flow maps around the player. Rather than every monster searching for its own path,
+// they step to whichever neighbouring tile has the lowest value: on the approach map that
+// leads to the player, on the flee map it leads away.
+pub struct AiMaps
+{
+    pub approach : DijkstraMap,
+    pub flee : DijkstraMap,
+    pub built_for : Option<(usize, i32)>,
+}
+
+impl Default for AiMaps
+{
+    fn default() -> AiMaps
+    {
+        AiMaps{ approach : DijkstraMap::new_empty(1, 1, MAX_DEPTH), flee : DijkstraMap::new_empty(1, 1, MAX_DEPTH),
+                built_for : None }
+    }
+}
+
+pub struct AiMapSystem {}
+
+impl<'a> System<'a> for AiMapSystem
+{
+    type SystemData = ( ReadExpect<'a, Map>,
+                        ReadExpect<'a, Point>,
+                        ReadExpect<'a, RunState>,
+                        WriteExpect<'a, AiMaps>,
+                        );
+
+    fn run(&mut self, data : Self::SystemData)
+    {
+        let (map, player_pos, runstate, mut ai_maps) = data;
+
+        // The maps only go stale once the player has acted, or if something else (like a trap)
+        // moves them to another tile or level in the meantime
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        let built_for = Some((player_idx, map.depth));
+        if *runstate != RunState::PlayerTurn && ai_maps.built_for == built_for { return; }
+
+        ai_maps.built_for = built_for;
+        ai_maps.approach = DijkstraMap::new(map.width, map.height, &[player_idx], &*map, MAX_DEPTH);
+        ai_maps.approach.map[player_idx] = 0.0;
+        ai_maps.flee = build_flee_map(&ai_maps.approach, &map);
+    }
+}
+
+// Simply climbing the approach map runs monsters into dead ends. Flipping and scaling it, then
+// letting the values settle again, makes the flee map favour routes that lead somewhere.
+fn build_flee_map(approach : &DijkstraMap, map : &Map) -> DijkstraMap
+{
+    let mut flee = DijkstraMap::new_empty(map.width, map.height, MAX_DEPTH);
+    let mut open_list : VecDeque<usize> = VecDeque::new();
+    for (idx, value) in approach.map.iter().enumerate()
+    {
+        if *value < f32::MAX
+        {
+            flee.map[idx] = *value * -1.2;
+            open_list.push_back(idx);
+        }
+    }
+
+    while let Some(idx) = open_list.pop_front()
+    {
+        for (exit, cost) in map.get_available_exits(idx)
+        {
+            let depth = flee.map[idx] + cost;
+            if depth < flee.map[exit]
+            {
+                flee.map[exit] = depth;
+                open_list.push_back(exit);
+            }
+        }
+    }
+
+    flee
+}
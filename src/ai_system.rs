@@ -1,6 +1,22 @@
 use specs::prelude::*;
-use super::{Viewshed, Monster, Map, Position, WantsToMelee, RunState, Confusion};
-use rltk::{Point};
+use super::{Viewshed, Monster, Map, Position, WantsToMelee, MyTurn, Door, BlocksTile,
+            BlocksVisibility, Renderable, EntityMoved, Faction, CombatStats, Pursuing, AiMaps,
+            Ability, WantsToUseItem, ProvidesHealing, Ranged, AreaOfEffect, Alertness, AlertState, Attributes,
+            gamesystem::{attr_bonus, stealth_dc, PURSUIT_TURNS}, open_door};
+use super::raws::{RAWS, Reaction, faction_reaction};
+use rltk::{Point, RandomNumberGenerator, DijkstraMap};
+
+// Monsters badly hurt enough to be below this fraction of their health run for it
+const FLEE_BELOW_HEALTH : f32 = 0.25;
+
+// Healers only bother with those below this fraction of their health
+const HEAL_BELOW_HEALTH : f32 = 0.5;
+
+// Monsters with ranged attacks try to stay at least this far from the player
+const KEEP_DISTANCE : f32 = 3.0;
+
+// How much harder it is to notice anything while asleep
+const SLEEP_PERCEPTION_PENALTY : i32 = 5;
 
 pub struct MonsterAI {}
 
@@ -8,68 +24,387 @@ impl<'a> System<'a> for MonsterAI
 {
     #[allow(clippy::type_complexity)]
     type SystemData = ( WriteExpect<'a, Map>,
-                        ReadExpect<'a, Point>,
-                        ReadExpect<'a, Entity>,
-                        ReadExpect<'a, RunState>,
+                        WriteExpect<'a, RandomNumberGenerator>,
                         Entities<'a>,
                         WriteStorage<'a, Viewshed>,
                         ReadStorage<'a, Monster>,
                         WriteStorage<'a, Position>,
                         WriteStorage<'a, WantsToMelee>,
-                        WriteStorage<'a, Confusion>,
+                        WriteStorage<'a, Door>,
+                        WriteStorage<'a, BlocksTile>,
+                        WriteStorage<'a, BlocksVisibility>,
+                        WriteStorage<'a, Renderable>,
+                        WriteStorage<'a, EntityMoved>,
+                        WriteStorage<'a, MyTurn>,
+                        ReadStorage<'a, Faction>,
+                        ReadStorage<'a, CombatStats>,
+                        WriteStorage<'a, Pursuing>,
+                        ReadExpect<'a, Entity>,
+                        ReadExpect<'a, AiMaps>,
+                        WriteStorage<'a, Ability>,
+                        WriteStorage<'a, WantsToUseItem>,
+                        ReadStorage<'a, ProvidesHealing>,
+                        ReadStorage<'a, Ranged>,
+                        ReadStorage<'a, AreaOfEffect>,
+                        WriteStorage<'a, Alertness>,
+                        ReadStorage<'a, Attributes>,
                         );
 
     fn run(&mut self, data : Self::SystemData)
     {
-        let (mut map, player_pos, player_entity, runstate, entities, mut viewshed, monster, 
-             mut position, mut wants_to_melee, mut confused) = data;
-        
-        if *runstate != RunState::MonsterTurn { return; }
+        let (mut map, mut rng, entities, mut viewshed, monster, mut position, mut wants_to_melee,
+             mut doors, mut blocks_movement, mut blocks_visibility, mut renderables, mut entity_moved,
+             mut turns, factions, combat_stats, mut pursuing, player_entity, ai_maps, mut abilities, mut wants_to_use,
+             healing, ranged, area_of_effect, mut alertness, attributes) = data;
 
-        for (entity, mut viewshed, _monster, mut pos) 
-         in (&entities, &mut viewshed, &monster, &mut position).join() 
+        let raws = RAWS.lock().unwrap();
+        let mut doors_opened = false;
+
+        let acting : Vec<Entity> = (&entities, &monster, &turns).join().map(|(e, _m, _t)| e).collect();
+        for entity in acting.iter()
         {
-            let mut can_act = true;
+            // Only act once per turn, however many times the systems run
+            turns.remove(*entity);
+
+            let my_pos = match position.get(*entity) { Some(pos) => Point::new(pos.x, pos.y), None => continue };
+            let my_faction = factions.get(*entity).map_or("", |f| f.name.as_str());
+
+            // Spotting the player means beating their stealth, which is harder when asleep.
+            // Sleepers who don't spot anything stay asleep.
+            let mut state = alertness.get(*entity).map_or(AlertState::Alert, |a| a.state);
+            let was_sleeping = state == AlertState::Sleeping;
+            let player_pos = position.get(*player_entity).map(|pos| Point::new(pos.x, pos.y));
+            let player_in_view = player_pos.is_some_and(|pos| viewshed.get(*entity).is_some_and(|vs| vs.visible_tiles.contains(&pos)));
+            if state != AlertState::Alert && player_in_view
+            {
+                let penalty = if state == AlertState::Sleeping { SLEEP_PERCEPTION_PENALTY } else { 0 };
+                let perception = rng.roll_dice(1, 20) + attributes.get(*entity).map_or(0, |a| attr_bonus(a.intelligence)) - penalty;
+                let stealth = stealth_dc(attributes.get(*player_entity).map_or(10, |a| a.quickness));
+                if perception >= stealth
+                {
+                    state = AlertState::Alert;
+                }
+            }
+            if was_sleeping
+            {
+                // Waking up takes a turn
+                if let Some(alert) = alertness.get_mut(*entity) { alert.state = state; }
+                continue;
+            }
 
-            let is_confused = confused.get_mut(entity);
-            if let Some(conf) = is_confused
+            // Look over everything in sight and decide how to feel about it
+            let mut attack_target : Option<(Entity, Point, f32)> = None;
+            let mut threats : Vec<(Entity, Point)> = Vec::new();
+            let mut pack_pursuit : Option<Pursuing> = None;
+            let mut hurt_ally : Option<(Point, f32)> = None;
+            let badly_hurt = |e : Entity| combat_stats.get(e)
+                .map(|s| s.hp as f32 / s.max_hp as f32)
+                .filter(|health| *health < HEAL_BELOW_HEALTH);
+            if let Some(vs) = viewshed.get(*entity)
             {
-                conf.turns -= 1;
-                if conf.turns < 1
+                for tile in vs.visible_tiles.iter()
                 {
-                    confused.remove(entity);
+                    let idx = map.xy_idx(tile.x, tile.y);
+                    for other in map.tile_content[idx].iter()
+                    {
+                        if *other == *entity || combat_stats.get(*other).is_none_or(|s| s.hp <= 0) { continue; }
+                        if *other == *player_entity && state != AlertState::Alert { continue; }
+                        let their_faction = match factions.get(*other) { Some(f) => f.name.as_str(), None => continue };
+
+                        match faction_reaction(my_faction, their_faction, &raws)
+                        {
+                            Reaction::Ignore =>
+                            {
+                                // Packmates that are on the hunt bring the others along
+                                if their_faction == my_faction
+                                {
+                                    if let Some(pursuit) = pursuing.get(*other)
+                                    {
+                                        pack_pursuit = Some(pursuit.clone());
+                                    }
+                                    if let Some(health) = badly_hurt(*other)
+                                    {
+                                        if hurt_ally.is_none_or(|(_p, h)| health < h)
+                                        {
+                                            hurt_ally = Some((*tile, health));
+                                        }
+                                    }
+                                }
+                            }
+                            Reaction::Flee => threats.push((*other, *tile)),
+                            Reaction::Attack =>
+                            {
+                                let distance = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *tile);
+                                if attack_target.is_none_or(|(_e, _p, d)| distance < d)
+                                {
+                                    attack_target = Some((*other, *tile, distance));
+                                }
+                            }
+                        }
+                    }
                 }
-                can_act = false;
             }
 
-            if can_act {
-                let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
-                if distance < 1.5 
+            // Anything that's badly hurt stops fighting and runs
+            if let (Some((target, target_pos, _distance)), Some(stats)) = (attack_target, combat_stats.get(*entity))
+            {
+                if (stats.hp as f32) < stats.max_hp as f32 * FLEE_BELOW_HEALTH
                 {
-                    wants_to_melee.insert(entity, WantsToMelee{ target: *player_entity })
-                                         .expect("Unable to insert Attack.");
+                    threats.push((target, target_pos));
+                    attack_target = None;
                 }
-                else if viewshed.visible_tiles.contains(&*player_pos)
+            }
+
+            if attack_target.is_none() && pursuing.get(*entity).is_none()
+            {
+                if let Some(pursuit) = pack_pursuit
                 {
-                    // path to the player
-                    let path = rltk::a_star_search(
-                        map.xy_idx(pos.x, pos.y) as i32,
-                        map.xy_idx(player_pos.x, player_pos.y) as i32,
-                        &mut *map
-                        );
+                    pursuing.insert(*entity, pursuit).expect("Unable to insert pursuit");
+                }
+            }
+
+            if let Some((_target, target_pos, _distance)) = attack_target
+            {
+                pursuing.insert(*entity, Pursuing{ x : target_pos.x, y : target_pos.y, turns : PURSUIT_TURNS })
+                    .expect("Unable to insert pursuit");
+            }
+
+            // Abilities recharge on their owner's turns
+            let mut ready_abilities : Vec<(Entity, i32)> = Vec::new();
+            let mut attack_range = 0.0;
+            for (ability_entity, ability) in (&entities, &mut abilities).join()
+            {
+                if ability.owner != *entity { continue; }
+                if healing.get(ability_entity).is_none()
+                {
+                    attack_range = f32::max(attack_range, ranged.get(ability_entity).map_or(0, |r| r.range) as f32);
+                }
+                if ability.cooldown_left > 0
+                {
+                    ability.cooldown_left -= 1;
+                }
+                else
+                {
+                    ready_abilities.push((ability_entity, ability.chance));
+                }
+            }
+
+            // Healing looks after itself first, then whichever packmate is worst off. Anything
+            // else is aimed at the current target, as long as the blast won't catch the caster.
+            let mut used_ability = false;
+            for (ability_entity, chance) in ready_abilities.iter()
+            {
+                let range = ranged.get(*ability_entity).map_or(0, |r| r.range) as f32;
+                let aim = if healing.get(*ability_entity).is_some()
+                {
+                    if badly_hurt(*entity).is_some()
+                    {
+                        Some(my_pos)
+                    }
+                    else
+                    {
+                        hurt_ally.map(|(pos, _health)| pos)
+                            .filter(|pos| rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *pos) <= range)
+                    }
+                }
+                else
+                {
+                    let radius = area_of_effect.get(*ability_entity).map_or(0, |a| a.radius) as f32;
+                    attack_target.filter(|(_t, _p, distance)| *distance <= range && *distance > radius)
+                        .map(|(_t, pos, _d)| pos)
+                };
+
+                if let Some(aim) = aim
+                {
+                    if rng.roll_dice(1, 100) <= *chance
+                    {
+                        wants_to_use.insert(*entity, WantsToUseItem{ item : *ability_entity, target : Some(aim) })
+                            .expect("Unable to insert intent");
+                        let ability = abilities.get_mut(*ability_entity).unwrap();
+                        ability.cooldown_left = ability.cooldown;
+                        used_ability = true;
+                        break;
+                    }
+                }
+            }
+
+            let my_idx = map.xy_idx(my_pos.x, my_pos.y);
+            let destination = if used_ability
+            {
+                None
+            }
+            else if !threats.is_empty()
+            {
+                // The flee map only knows about the player; anything else is run from directly
+                if threats.iter().any(|(threat, _pos)| *threat == *player_entity)
+                {
+                    downhill_step(&ai_maps.flee, my_idx, &map)
+                }
+                else
+                {
+                    let threat_positions : Vec<Point> = threats.iter().map(|(_threat, pos)| *pos).collect();
+                    flee_step(&map, my_pos, &threat_positions)
+                }
+            }
+            else if let Some((target, target_pos, distance)) = attack_target
+            {
+                let is_player = target == *player_entity;
+                let backing_off = if attack_range > 1.5 && is_player && distance < KEEP_DISTANCE
+                {
+                    downhill_step(&ai_maps.flee, my_idx, &map)
+                }
+                else
+                {
+                    None
+                };
+
+                if backing_off.is_some()
+                {
+                    backing_off
+                }
+                else if distance < 1.5
+                {
+                    wants_to_melee.insert(*entity, WantsToMelee{ target }).expect("Unable to insert Attack.");
+                    None
+                }
+                else if distance <= attack_range
+                {
+                    // Close enough to shoot, so hold still and wait for the next chance
+                    None
+                }
+                else if is_player
+                {
+                    downhill_step(&ai_maps.approach, my_idx, &map)
+                }
+                else
+                {
+                    path_step(&map, my_pos, target_pos)
+                }
+            }
+            else if let Some(pursuit) = pursuing.get_mut(*entity)
+            {
+                // Lost sight of its prey, so head for where it was last seen until the trail goes cold
+                let last_seen = Point::new(pursuit.x, pursuit.y);
+                pursuit.turns -= 1;
+                let step = if pursuit.turns > 0 && last_seen != my_pos { path_step(&map, my_pos, last_seen) } else { None };
+                if step.is_none()
+                {
+                    pursuing.remove(*entity);
+                }
+                step
+            }
+            else
+            {
+                // Nothing worth bothering with in sight, so amble about
+                wander_step(&map, &mut rng, my_pos)
+            };
 
-                    if path.success && path.steps.len() > 1
+            // Anything still chasing or running stays on its guard; the rest go back to wandering
+            if let Some(alert) = alertness.get_mut(*entity)
+            {
+                alert.state = if pursuing.get(*entity).is_some() || !threats.is_empty() { AlertState::Alert } else { AlertState::Wandering };
+            }
+
+            if let Some(destination) = destination
+            {
+                let next_door = map.tile_content[destination].iter()
+                    .find(|e| doors.get(**e).is_some_and(|door| !door.open))
+                    .copied();
+
+                if let Some(door_entity) = next_door
+                {
+                    // A closed door is in the way, so spend the turn opening it
+                    open_door(door_entity, destination, &mut map, &mut doors, &mut blocks_movement,
+                              &mut blocks_visibility, &mut renderables);
+                    doors_opened = true;
+                }
+                else if !map.blocked[destination]
+                {
+                    let pos = position.get_mut(*entity).unwrap();
+                    let mut idx = map.xy_idx(pos.x, pos.y);
+                    map.blocked[idx] = false;
+                    pos.x = destination as i32 % map.width;
+                    pos.y = destination as i32 / map.width;
+                    idx = map.xy_idx(pos.x, pos.y);
+                    map.blocked[idx] = true;
+                    if let Some(vs) = viewshed.get_mut(*entity)
                     {
-                        let mut idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = false;
-                        pos.x = path.steps[1] as i32 % map.width;
-                        pos.y = path.steps[1] as i32 / map.width;
-                        idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = true;
-                        viewshed.dirty = true;
+                        vs.dirty = true;
                     }
+                    entity_moved.insert(*entity, EntityMoved{}).expect("Unable to insert marker");
                 }
             }
         }
+
+        // Everyone may be able to see further now
+        if doors_opened
+        {
+            for viewshed in (&mut viewshed).join()
+            {
+                viewshed.dirty = true;
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+// Steps to the lowest valued neighbour on one of the shared flow maps
+fn downhill_step(flow : &DijkstraMap, idx : usize, map : &Map) -> Option<usize>
+{
+    DijkstraMap::find_lowest_exit(flow, idx, map).filter(|exit| flow.map[*exit] < f32::MAX)
+}
+
+fn path_step(map : &Map, from : Point, to : Point) -> Option<usize>
+{
+    let path = rltk::a_star_search(map.xy_idx(from.x, from.y) as i32, map.xy_idx(to.x, to.y) as i32, map);
+    if path.success && path.steps.len() > 1
+    {
+        Some(path.steps[1])
+    }
+    else
+    {
+        None
+    }
+}
+
+// The neighbouring tiles something standing at pos could step onto
+fn open_neighbours(map : &Map, pos : Point) -> Vec<usize>
+{
+    let mut exits = Vec::new();
+    for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)].iter()
+    {
+        let (x, y) = (pos.x + dx, pos.y + dy);
+        if map.is_exit_valid(x, y)
+        {
+            exits.push(map.xy_idx(x, y));
+        }
+    }
+    exits
+}
+
+// Steps to whichever neighbouring tile is furthest from the nearest threat
+fn flee_step(map : &Map, pos : Point, threats : &[Point]) -> Option<usize>
+{
+    let nearest_threat = |p : Point| threats.iter()
+        .map(|t| rltk::DistanceAlg::Pythagoras.distance2d(p, *t))
+        .fold(f32::MAX, f32::min);
+
+    let mut best = None;
+    let mut best_distance = nearest_threat(pos);
+    for idx in open_neighbours(map, pos)
+    {
+        let distance = nearest_threat(Point::new(idx as i32 % map.width, idx as i32 / map.width));
+        if distance > best_distance
+        {
+            best = Some(idx);
+            best_distance = distance;
+        }
+    }
+    best
+}
+
+fn wander_step(map : &Map, rng : &mut RandomNumberGenerator, pos : Point) -> Option<usize>
+{
+    let exits = open_neighbours(map, pos);
+    if exits.is_empty() { return None; }
+    Some(exits[(rng.roll_dice(1, exits.len() as i32) - 1) as usize])
+}
@@ -0,0 +1,125 @@
+use rltk::{ RGB, Rltk, Point };
+use specs::prelude::*;
+use super::{Map, TileType, Position, Renderable, Hidden};
+
+// Rows at the bottom of the console that belong to the UI rather than the map view
+const UI_HEIGHT : i32 = 7;
+
+// Returns the map coordinates shown in the top left corner of the screen, and the size of the view.
+// The view follows the player, but stops at the map edges so it never shows empty space beyond them.
+pub fn get_screen_bounds(ecs : &World, ctx : &mut Rltk) -> (i32, i32, i32, i32)
+{
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let (x_chars, y_chars) = ctx.get_char_size();
+    let view_width = x_chars as i32;
+    let view_height = y_chars as i32 - UI_HEIGHT;
+
+    let min_x = camera_axis_start(player_pos.x, view_width, map.width);
+    let min_y = camera_axis_start(player_pos.y, view_height, map.height);
+
+    (min_x, min_y, view_width, view_height)
+}
+
+fn camera_axis_start(player : i32, view_size : i32, map_size : i32) -> i32
+{
+    if map_size <= view_size
+    {
+        0
+    }
+    else
+    {
+        i32::max(0, i32::min(player - view_size / 2, map_size - view_size))
+    }
+}
+
+// Converts a position on the screen into map coordinates, or None if it lies outside the map view
+pub fn screen_to_map(ecs : &World, ctx : &mut Rltk, screen_x : i32, screen_y : i32) -> Option<Point>
+{
+    let (min_x, min_y, view_width, view_height) = get_screen_bounds(ecs, ctx);
+    if screen_x < 0 || screen_x >= view_width || screen_y < 0 || screen_y >= view_height { return None; }
+
+    let map = ecs.fetch::<Map>();
+    let map_x = screen_x + min_x;
+    let map_y = screen_y + min_y;
+    if map_x >= map.width || map_y >= map.height { return None; }
+
+    Some(Point::new(map_x, map_y))
+}
+
+pub fn render_camera(ecs : &World, ctx : &mut Rltk)
+{
+    let map = ecs.fetch::<Map>();
+    let (min_x, min_y, view_width, view_height) = get_screen_bounds(ecs, ctx);
+
+    // Render the part of the map inside the view
+    for screen_y in 0 .. view_height
+    {
+        for screen_x in 0 .. view_width
+        {
+            let tx = screen_x + min_x;
+            let ty = screen_y + min_y;
+            if tx < map.width && ty < map.height
+            {
+                let idx = map.xy_idx(tx, ty);
+                if map.revealed_tiles[idx]
+                {
+                    let (glyph, fg, bg) = get_tile_glyph(idx, &map);
+                    ctx.set(screen_x, screen_y, fg, bg, glyph);
+                }
+            }
+        }
+    }
+
+    // Render the entities the player can see
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut data = (&positions, &renderables, !&hidden).join().collect::<Vec<_>>();
+    data.sort_by_key(|&a| std::cmp::Reverse(a.1.render_order));
+    for (pos, render, _hidden) in data.iter()
+    {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if map.visible_tiles[idx]
+        {
+            let screen_x = pos.x - min_x;
+            let screen_y = pos.y - min_y;
+            if screen_x >= 0 && screen_x < view_width && screen_y >= 0 && screen_y < view_height
+            {
+                ctx.set(screen_x, screen_y, render.fg, render.bg, render.glyph);
+            }
+        }
+    }
+}
+
+fn get_tile_glyph(idx : usize, map : &Map) -> (rltk::FontCharType, RGB, RGB)
+{
+    let glyph;
+    let mut fg;
+    match map.tiles[idx]
+    {
+        TileType::Floor => {
+            glyph = rltk::to_cp437('.');
+            fg = RGB::from_f32(0.0, 0.5, 0.5);
+        }
+        TileType::Wall => {
+            glyph =  rltk::to_cp437('#');
+            fg = RGB::from_f32(0.0, 1.0, 0.0);
+        }
+        TileType::DownStairs => {
+            glyph = rltk::to_cp437('>');
+            fg = RGB::from_f32(0.0, 1.0, 1.0);
+        }
+        TileType::UpStairs => {
+            glyph = rltk::to_cp437('<');
+            fg = RGB::from_f32(0.0, 1.0, 1.0);
+        }
+    }
+    if !map.visible_tiles[idx]
+    {
+        fg = fg.to_greyscale();
+    }
+
+    (glyph, fg, RGB::from_f32(0., 0., 0.))
+}
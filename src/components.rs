@@ -12,6 +12,15 @@ pub struct Position
     pub y: i32,
 }
 
+// Where an entity is parked while the player is on a different level
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct OtherLevelPosition
+{
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Renderable 
 {
@@ -39,7 +48,7 @@ pub struct Monster {}
 pub struct Item {}
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
-pub enum EquipmentSlot { Melee, Shield }
+pub enum EquipmentSlot { Melee, Shield, Ranged, Head, Torso, Legs, Feet, Hands, Amulet, Ring }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Equippable
@@ -47,6 +56,10 @@ pub struct Equippable
     pub slot : EquipmentSlot
 }
 
+// Wielded in the melee slot, but needs the shield hand as well
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct TwoHanded {}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InBackpack
 {
@@ -60,10 +73,43 @@ pub struct Equipped
     pub slot : EquipmentSlot
 }
 
+// A weapon adds to the wielder's attack roll, and rolls its own dice for damage
 #[derive(Component, ConvertSaveload, Clone)]
-pub struct MeleePowerBonus 
+pub struct MeleeWeapon
 {
-    pub power : i32
+    pub hit_bonus : i32,
+    pub damage_n_dice : i32,
+    pub damage_die_type : i32,
+    pub damage_bonus : i32,
+}
+
+// A bow or crossbow: it needs the matching ammunition in the backpack to fire
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct RangedWeapon
+{
+    pub range : i32,
+    pub hit_bonus : i32,
+    pub damage_n_dice : i32,
+    pub damage_die_type : i32,
+    pub damage_bonus : i32,
+    pub ammo : String,
+}
+
+// A stack of arrows or bolts. Picking up more of the same type adds to the stack.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Ammunition
+{
+    pub ammo_type : String,
+    pub quantity : i32,
+}
+
+// What an entity hits with when it isn't wielding anything
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct NaturalAttack
+{
+    pub damage_n_dice : i32,
+    pub damage_die_type : i32,
+    pub damage_bonus : i32,
 }
 
 #[derive(Component, ConvertSaveload, Clone)]
@@ -72,6 +118,12 @@ pub struct DefenseBonus
     pub defense : i32
 }
 
+#[derive(Component, ConvertSaveload, Clone)]
+pub struct SpeedBonus
+{
+    pub speed : i32
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToPickupItem
 {
@@ -86,6 +138,12 @@ pub struct WantsToUseItem
     pub target : Option<rltk::Point>
 }
 
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToShoot
+{
+    pub target : rltk::Point
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToRemoveItem
 {
@@ -116,10 +174,41 @@ pub struct AreaOfEffect
     pub radius : i32
 }
 
+// Something a monster can do besides hitting things. It lives on its own entity, carrying the
+// same effect components as a scroll, and is used through WantsToUseItem just like one.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Ability
+{
+    pub owner : Entity,
+    pub cooldown : i32,
+    pub cooldown_left : i32,
+    pub chance : i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusKind { Confused, Poisoned, Burning, Asleep, Slowed, Regenerating }
+
+// Magnitude is the damage or healing per turn, for the kinds that have one
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StatusEffect
+{
+    pub kind : StatusKind,
+    pub turns : i32,
+    pub magnitude : i32,
+}
+
+// Everything currently affecting an entity. Each effect ticks down on its owner's turns.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct StatusEffects
+{
+    pub effects : Vec<StatusEffect>
+}
+
+// Put on items and traps: whoever they are used on or triggered by gets these effects
 #[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Confusion
+pub struct InflictsStatus
 {
-    pub turns : i32
+    pub effects : Vec<StatusEffect>
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -134,13 +223,134 @@ pub struct Consumable {}
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct BlocksVisibility {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Door
+{
+    pub open : bool
+}
+
+// Not drawn or shown in tooltips until the player spots it
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Hidden {}
+
+// Fires when anything steps onto the same tile
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct EntryTrigger {}
+
+// Removed once it has fired
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct SingleActivation {}
+
+// Sends whoever triggers it to a random spot on the level
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {}
+
+// Flags an entity that moved this turn, so entry triggers can check where it ended up
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct EntityMoved {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Experience
+{
+    pub level : i32,
+    pub xp : i32,
+}
+
+// How much experience killing this is worth
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct GivesExperience
+{
+    pub xp : i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum HungerState { WellFed, Normal, Hungry, Starving }
+
+// Counts down each turn; when it runs out the owner drops to the next hunger state
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct HungerClock
+{
+    pub state : HungerState,
+    pub duration : i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesFood {}
+
+// Names the raws loot table rolled on when this dies
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct LootTable
+{
+    pub table : String,
+}
+
+// Where a monster last saw something it wanted to fight, and how much longer it will keep
+// looking once it has lost sight of it
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Pursuing
+{
+    pub x : i32,
+    pub y : i32,
+    pub turns : i32,
+}
+
+// Monsters start out asleep or wandering about, and only turn alert once they notice
+// something worth chasing
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum AlertState { Sleeping, Wandering, Alert }
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Alertness
+{
+    pub state : AlertState,
+}
+
+// Which side something is on; the raws faction table decides how the sides get along
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Faction
+{
+    pub name : String,
+}
+
+// Energy builds up by speed each tick; spending it is what lets an entity take its turn
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Initiative
+{
+    pub speed : i32,
+    pub energy : i32,
+}
+
+// Marks the entities that get to act this tick
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MyTurn {}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct CombatStats
 {
     pub max_hp : i32,
     pub hp : i32,
+}
+
+// Might drives melee hits and damage, fitness hit points, quickness armor class and intelligence magic
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Attributes
+{
+    pub might : i32,
+    pub fitness : i32,
+    pub quickness : i32,
+    pub intelligence : i32,
+}
+
+// Trained ability, added straight onto the attribute bonus that goes with it
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Skills
+{
+    pub melee : i32,
     pub defense : i32,
-    pub power : i32,
+    pub magic : i32,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
@@ -149,24 +359,26 @@ pub struct WantsToMelee
     pub target : Entity
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
+// Each hit is the damage and whoever dealt it, if anyone did. This only lives until the
+// damage system runs, so it is never saved.
+#[derive(Component, Debug, Clone)]
 pub struct SufferDamage 
 {
-    pub amount : Vec<i32>
+    pub amount : Vec<(i32, Option<Entity>)>
 }
 
 // TODO: this seems wrong...  should be part of the damage system maybe?
 impl SufferDamage 
 {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32)
+    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32, from: Option<Entity>)
     {
         if let Some(suffering) = store.get_mut(victim)
         {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, from));
         }
         else
         {
-            let dmg = SufferDamage { amount : vec![amount] };
+            let dmg = SufferDamage { amount : vec![(amount, from)] };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
@@ -178,5 +390,8 @@ pub struct SerializeMe;
 // Special component that exists to help serialize the game data
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
-    pub map : super::map::Map
+    pub map : super::map::Map,
+    pub seed : super::run_seed::RunSeed,
+    pub rng : rltk::RandomNumberGenerator,
+    pub dungeon : super::dungeon_master::DungeonMaster
 }
\ No newline at end of file
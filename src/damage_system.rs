@@ -1,24 +1,93 @@
 use specs::prelude::*;
-use super::{CombatStats, SufferDamage, Player, Name, gamelog::GameLog, RunState };
-use rltk::{console};
+use super::{CombatStats, SufferDamage, Player, Name, gamelog::GameLog, RunState, Experience,
+            GivesExperience, Attributes, Skills, gamesystem::max_hp_at_level, Position, Equipped,
+            InBackpack, LootTable, StatusEffects, StatusKind, Ability, Alertness, AlertState };
+use super::raws::{RAWS, SpawnType, spawn_named_entity, get_item_drop};
+use rltk::RandomNumberGenerator;
+
+// Experience is kept as a running total; this is the total needed to reach the next level
+pub fn xp_for_next_level(level : i32) -> i32
+{
+    50 * level * (level + 1)
+}
 
 pub struct DamageSystem {}
 
 impl <'a> System<'a> for DamageSystem
 {
-    type SystemData = ( WriteStorage<'a, CombatStats>,
-                        WriteStorage<'a, SufferDamage>, );
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        WriteStorage<'a, CombatStats>,
+                        WriteStorage<'a, SufferDamage>,
+                        WriteStorage<'a, Experience>,
+                        ReadStorage<'a, GivesExperience>,
+                        ReadStorage<'a, Player>,
+                        ReadStorage<'a, Attributes>,
+                        WriteStorage<'a, Skills>,
+                        WriteStorage<'a, StatusEffects>,
+                        WriteExpect<'a, GameLog>,
+                        WriteStorage<'a, Alertness>, );
 
     fn run (&mut self, data : Self::SystemData)
     {
-        let (mut stats, mut damage) = data;
+        let (entities, mut stats, mut damage, mut experience, gives_experience, players, attributes,
+             mut skills, mut statuses, mut log, mut alertness) = data;
 
-        for (mut stats, damage) in (&mut stats, &damage).join()
+        let mut xp_gains : Vec<(Entity, i32)> = Vec::new();
+        for (victim, stats, damage) in (&entities, &mut stats, &damage).join()
         {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+            // Getting hurt wakes you up
+            if let Some(status) = statuses.get_mut(victim)
+            {
+                status.effects.retain(|e| e.kind != StatusKind::Asleep);
+            }
+            if let Some(alert) = alertness.get_mut(victim)
+            {
+                alert.state = AlertState::Alert;
+            }
+
+            for (amount, from) in damage.amount.iter()
+            {
+                let was_alive = stats.hp > 0;
+                stats.hp -= amount;
+
+                // Whoever lands the killing blow gets the credit
+                if was_alive && stats.hp < 1
+                {
+                    if let (Some(killer), Some(worth)) = (from, gives_experience.get(victim))
+                    {
+                        xp_gains.push((*killer, worth.xp));
+                    }
+                }
+            }
         }
 
         damage.clear();
+
+        for (killer, xp) in xp_gains.iter()
+        {
+            if let (Some(exp), Some(killer_stats)) = (experience.get_mut(*killer), stats.get_mut(*killer))
+            {
+                exp.xp += xp;
+                while exp.xp >= xp_for_next_level(exp.level)
+                {
+                    exp.level += 1;
+                    if let Some(attr) = attributes.get(*killer)
+                    {
+                        killer_stats.max_hp = max_hp_at_level(attr.fitness, exp.level);
+                    }
+                    killer_stats.hp = killer_stats.max_hp;
+                    if let Some(skill) = skills.get_mut(*killer)
+                    {
+                        skill.melee += 1;
+                    }
+                    if players.get(*killer).is_some()
+                    {
+                        log.entries.push(format!("Level up! You are now level {}.", exp.level));
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -57,6 +126,67 @@ pub fn delete_the_dead(ecs : &mut World)
         }
     }
 
+    // Whatever the dead were carrying falls to the floor where they died, along with a roll
+    // on their loot table
+    let mut to_spawn : Vec<(String, Position)> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let mut equipped = ecs.write_storage::<Equipped>();
+        let mut carried = ecs.write_storage::<InBackpack>();
+        let mut positions = ecs.write_storage::<Position>();
+        let loot_tables = ecs.read_storage::<LootTable>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+
+        let mut to_drop : Vec<(Entity, Position)> = Vec::new();
+        for victim in dead.iter()
+        {
+            if let Some(pos) = positions.get(*victim)
+            {
+                for (item, equipped_by) in (&entities, &equipped).join()
+                {
+                    if equipped_by.owner == *victim { to_drop.push((item, pos.clone())); }
+                }
+                for (item, backpack) in (&entities, &carried).join()
+                {
+                    if backpack.owner == *victim { to_drop.push((item, pos.clone())); }
+                }
+
+                if let Some(table) = loot_tables.get(*victim)
+                {
+                    if let Some(drop) = get_item_drop(&RAWS.lock().unwrap(), &mut rng, &table.table)
+                    {
+                        to_spawn.push((drop, pos.clone()));
+                    }
+                }
+            }
+        }
+
+        for (item, pos) in to_drop
+        {
+            equipped.remove(item);
+            carried.remove(item);
+            positions.insert(item, pos).expect("Unable to insert position");
+        }
+    }
+
+    for (drop, pos) in to_spawn.iter()
+    {
+        spawn_named_entity(&RAWS.lock().unwrap(), ecs, drop, SpawnType::AtPosition{ x : pos.x, y : pos.y });
+    }
+
+    // Abilities die with whoever had them
+    {
+        let entities = ecs.entities();
+        let abilities = ecs.read_storage::<Ability>();
+        for (ability_entity, ability) in (&entities, &abilities).join()
+        {
+            if dead.contains(&ability.owner)
+            {
+                entities.delete(ability_entity).expect("Unable to delete");
+            }
+        }
+    }
+
     for victim in dead
     {
         ecs.delete_entity(victim).expect("Unable to delete");
@@ -0,0 +1,90 @@
+use specs::prelude::*;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use super::{Map, Position, OtherLevelPosition};
+
+// Keeps every level the player has visited, so it can be returned to exactly as it was left.
+// The entities on those levels stay in the ECS, parked with an OtherLevelPosition.
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct DungeonMaster
+{
+    maps : HashMap<i32, Map>
+}
+
+impl DungeonMaster
+{
+    pub fn new() -> DungeonMaster
+    {
+        DungeonMaster{ maps : HashMap::new() }
+    }
+
+    pub fn store_map(&mut self, map : &Map)
+    {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    pub fn get_map(&self, depth : i32) -> Option<Map>
+    {
+        if let Some(map) = self.maps.get(&depth)
+        {
+            let mut result = map.clone();
+            result.tile_content = vec![Vec::new(); (result.width * result.height) as usize];
+            Some(result)
+        }
+        else
+        {
+            None
+        }
+    }
+}
+
+// Takes everything on the current level except the player off the map
+pub fn freeze_level_entities(ecs : &mut World)
+{
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let player_entity = ecs.fetch::<Entity>();
+    let map_depth = ecs.fetch::<Map>().depth;
+
+    let mut pos_to_delete : Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &positions).join()
+    {
+        if entity != *player_entity
+        {
+            other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, depth: map_depth })
+                .expect("Insert fail");
+            pos_to_delete.push(entity);
+        }
+    }
+
+    for p in pos_to_delete.iter()
+    {
+        positions.remove(*p);
+    }
+}
+
+// Puts everything that was left on the current level back where it was
+pub fn thaw_level_entities(ecs : &mut World)
+{
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let map_depth = ecs.fetch::<Map>().depth;
+
+    let mut pos_to_delete : Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &other_level_positions).join()
+    {
+        if pos.depth == map_depth
+        {
+            positions.insert(entity, Position{ x: pos.x, y: pos.y })
+                .expect("Insert fail");
+            pos_to_delete.push(entity);
+        }
+    }
+
+    for p in pos_to_delete.iter()
+    {
+        other_level_positions.remove(*p);
+    }
+}
@@ -0,0 +1,85 @@
+// The rules that turn attributes into numbers the rest of the game uses
+use specs::prelude::*;
+use specs::storage::MaskedStorage;
+use std::ops::Deref;
+use rltk::{RandomNumberGenerator, Point};
+use super::{Position, Alertness, AlertState, Pursuing};
+
+// An attribute of 10 is average; every two points either side is worth one point of bonus
+pub fn attr_bonus(value : i32) -> i32
+{
+    (value - 10).div_euclid(2)
+}
+
+// Fitness sets the base, and each level adds a few more hit points on top
+pub fn max_hp_at_level(fitness : i32, level : i32) -> i32
+{
+    i32::max(1, fitness + level * (4 + attr_bonus(fitness)))
+}
+
+// Armor class of something with no defense at all
+const BASE_ARMOR_CLASS : i32 = 10;
+
+// What an attack roll has to reach to land a hit
+pub fn armor_class(quickness : i32, defense_skill : i32, armor : i32) -> i32
+{
+    BASE_ARMOR_CLASS + attr_bonus(quickness) + defense_skill + armor
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum AttackResult { Fumble, Miss, Hit, Critical }
+
+// A natural 1 always misses and a natural 20 always hits, whatever the bonuses
+pub fn roll_attack(rng : &mut RandomNumberGenerator, hit_bonus : i32, armor_class : i32) -> AttackResult
+{
+    let natural_roll = rng.roll_dice(1, 20);
+    if natural_roll == 1 { AttackResult::Fumble }
+    else if natural_roll == 20 { AttackResult::Critical }
+    else if natural_roll + hit_bonus >= armor_class { AttackResult::Hit }
+    else { AttackResult::Miss }
+}
+
+// A critical rolls the damage dice twice. Any hit does at least one point.
+pub fn roll_damage(rng : &mut RandomNumberGenerator, dice : (i32, i32, i32), critical : bool) -> i32
+{
+    let (n_dice, die_type, bonus) = dice;
+    let mut damage = rng.roll_dice(n_dice, die_type) + bonus;
+    if critical
+    {
+        damage += rng.roll_dice(n_dice, die_type);
+    }
+    i32::max(1, damage)
+}
+
+// What a monster's perception roll has to reach to notice someone sneaking about
+const BASE_STEALTH : i32 = 10;
+
+pub fn stealth_dc(quickness : i32) -> i32
+{
+    BASE_STEALTH + attr_bonus(quickness)
+}
+
+// How many turns a monster keeps hunting for something it has lost sight of
+pub const PURSUIT_TURNS : i32 = 20;
+
+// How far the sound of a fight carries
+const NOISE_RADIUS : f32 = 8.0;
+
+// The sound of a fight wakes anything nearby and brings it over to see what's going on
+pub fn make_noise<D>(at : Point, positions : &Storage<Position, D>, alertness : &mut WriteStorage<Alertness>,
+                     pursuing : &mut WriteStorage<Pursuing>, entities : &Entities)
+    where D : Deref<Target = MaskedStorage<Position>>
+{
+    for (entity, pos, alert) in (entities, positions, alertness).join()
+    {
+        if rltk::DistanceAlg::Pythagoras.distance2d(at, Point::new(pos.x, pos.y)) > NOISE_RADIUS { continue; }
+        if alert.state != AlertState::Alert
+        {
+            alert.state = AlertState::Alert;
+            if pursuing.get(entity).is_none()
+            {
+                pursuing.insert(entity, Pursuing{ x : at.x, y : at.y, turns : PURSUIT_TURNS }).expect("Unable to insert pursuit");
+            }
+        }
+    }
+}
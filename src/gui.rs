@@ -1,7 +1,9 @@
 use rltk::{ RGB, Rltk, VirtualKeyCode };
 use specs::prelude::*;
 use super::{ CombatStats, Player, gamelog::GameLog, Map, Name, Position, Point, 
-    State, InBackpack, Viewshed, RunState, Equipped };
+    State, InBackpack, Viewshed, RunState, Equipped, RunSeed, Hidden, Experience, camera,
+    HungerClock, HungerState, Ammunition, EquipmentSlot, TwoHanded, StatusEffects, StatusKind, Alertness, AlertState,
+    damage_system::xp_for_next_level, status_system::status_name };
 
 pub fn draw_ui(ecs: &World, ctx : &mut Rltk)
 {
@@ -12,24 +14,53 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk)
     ctx.print_color(2, 43, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &depth);
 
     let combat_stats = ecs.read_storage::<CombatStats>();
+    let experience = ecs.read_storage::<Experience>();
     let players = ecs.read_storage::<Player>();
-    for (_player, stats) in (&players, &combat_stats).join()
+    for (_player, stats, exp) in (&players, &combat_stats, &experience).join()
     {
+        let level = format!(" Level {} ({}/{} XP) ", exp.level, exp.xp, xp_for_next_level(exp.level));
+        ctx.print_color(11, 43, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &level);
+
+        // Health sits far enough along to leave the level text room for six figure XP totals
         let health = format!(" HP: {} / {} ", stats.hp, stats.max_hp);
-        ctx.print_color(12, 43, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &health);
+        ctx.print_color(42, 43, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), &health);
 
-        ctx.draw_bar_horizontal(28, 43, 51, 
+        ctx.draw_bar_horizontal(58, 43, 21, 
                                 stats.hp, stats.max_hp, 
                                 RGB::named(rltk::RED), RGB::named(rltk::BLACK));
     }
 
+    // Hunger sits on the bottom border, and only shows up when it isn't normal
+    let hunger = ecs.read_storage::<HungerClock>();
+    for (_player, hc) in (&players, &hunger).join()
+    {
+        match hc.state
+        {
+            HungerState::WellFed => ctx.print_color(69, 49, RGB::named(rltk::GREEN), RGB::named(rltk::BLACK), " Well Fed "),
+            HungerState::Normal => {}
+            HungerState::Hungry => ctx.print_color(71, 49, RGB::named(rltk::ORANGE), RGB::named(rltk::BLACK), " Hungry "),
+            HungerState::Starving => ctx.print_color(69, 49, RGB::named(rltk::RED), RGB::named(rltk::BLACK), " Starving "),
+        }
+    }
+
+    // Status effects run along the left of the bottom border
+    let statuses = ecs.read_storage::<StatusEffects>();
+    for (_player, status) in (&players, &statuses).join()
+    {
+        let mut x = 2;
+        for effect in status.effects.iter()
+        {
+            let label = format!(" {} ({}) ", status_name(effect.kind), effect.turns);
+            ctx.print_color(x, 49, status_color(effect.kind), RGB::named(rltk::BLACK), &label);
+            x += label.len() as i32;
+        }
+    }
+
     let log = ecs.fetch::<GameLog>();
 
-    let mut y = 44;
-    for s in log.entries.iter().rev()
+    for (y, s) in (44..49).zip(log.entries.iter().rev())
     {
-        if y < 49 {ctx.print(2, y, s);}
-        y += 1;
+        ctx.print(2, y, s);
     }
     
     // Draw mouse cursor
@@ -40,21 +71,57 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk)
     draw_tooltips(ecs, ctx);
 }
 
+fn status_color(kind : StatusKind) -> RGB
+{
+    match kind
+    {
+        StatusKind::Confused => RGB::named(rltk::MAGENTA),
+        StatusKind::Poisoned => RGB::named(rltk::GREEN),
+        StatusKind::Burning => RGB::named(rltk::ORANGE),
+        StatusKind::Asleep => RGB::named(rltk::LIGHT_BLUE),
+        StatusKind::Slowed => RGB::named(rltk::GREY),
+        StatusKind::Regenerating => RGB::named(rltk::PINK),
+    }
+}
+
 fn draw_tooltips(ecs: &World, ctx : &mut Rltk) 
 {
+    let mouse_pos = ctx.mouse_pos();
+    let mouse_map_pos = camera::screen_to_map(ecs, ctx, mouse_pos.0, mouse_pos.1);
+    let mouse_map_pos = if let Some(pos) = mouse_map_pos { pos } else { return; };
+
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Name>();
     let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    let alertness = ecs.read_storage::<Alertness>();
+    let entities = ecs.entities();
 
-    let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height { return; }
     let mut tooltip : Vec<String> = Vec::new();
-    for (name, position) in (&names, &positions).join()
+    for (entity, name, position, _hidden) in (&entities, &names, &positions, !&hidden).join()
     {
         let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx]
+        if position.x == mouse_map_pos.x && position.y == mouse_map_pos.y && map.visible_tiles[idx]
         {
-            tooltip.push(name.name.to_string());
+            let mut labels : Vec<&str> = Vec::new();
+            if alertness.get(entity).is_some_and(|a| a.state == AlertState::Sleeping)
+            {
+                labels.push("Zz");
+            }
+            if let Some(status) = statuses.get(entity)
+            {
+                labels.extend(status.effects.iter().map(|e| status_name(e.kind)));
+            }
+
+            if labels.is_empty()
+            {
+                tooltip.push(name.name.to_string());
+            }
+            else
+            {
+                tooltip.push(format!("{} ({})", name.name, labels.join(", ")));
+            }
         }
     }
 
@@ -74,39 +141,35 @@ fn draw_tooltips(ecs: &World, ctx : &mut Rltk)
         {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter()
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter())
             {
                 ctx.print_color(left_x, y, RGB::named(rltk::WHITE), RGB::named(rltk::GREY), s);
                 let padding = (width - s.len() as i32)-1;
                 for i in 0..padding
                 {
                     ctx.print_color(arrow_pos.x - i, y, 
-                        RGB::named(rltk::WHITE), RGB::named(rltk::GREY), &" ".to_string());
+                        RGB::named(rltk::WHITE), RGB::named(rltk::GREY), " ");
                 }
-                y += 1;
             }
             ctx.print_color(arrow_pos.x, arrow_pos.y, 
-                RGB::named(rltk::WHITE), RGB::named(rltk::GREY), &"->".to_string());
+                RGB::named(rltk::WHITE), RGB::named(rltk::GREY), "->");
         }
         else
         {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter()
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter())
             {
                 ctx.print_color(left_x + 1, y, RGB::named(rltk::WHITE), RGB::named(rltk::GREY), s);
                 let padding = (width - s.len() as i32) - 1;
                 for i in 0..padding
                 {
                     ctx.print_color(arrow_pos.x + 1 + i, y, 
-                        RGB::named(rltk::WHITE), RGB::named(rltk::GREY), &" ".to_string());
+                        RGB::named(rltk::WHITE), RGB::named(rltk::GREY), " ");
                 }
-                y += 1;
             }
             ctx.print_color(arrow_pos.x, arrow_pos.y, 
-                RGB::named(rltk::WHITE), RGB::named(rltk::GREY), &"<-".to_string());
+                RGB::named(rltk::WHITE), RGB::named(rltk::GREY), "<-");
         }
     }
 }
@@ -119,28 +182,31 @@ pub fn show_inventory(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Opti
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
+    let ammunition = gs.ecs.read_storage::<Ammunition>();
     let entities = gs.ecs.entities();
 
     let inventory = (&backpack, &names).join().filter(|item| item.0.owner == *player_entity );
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(15, y-2, 31, (count + 3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
     ctx.print_color(18, y-2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Inventory");
     ctx.print_color(18, y+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
 
     let mut equippable : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity ) 
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity ).enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
         ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97+j as rltk::FontCharType);
         ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        match ammunition.get(entity)
+        {
+            None => ctx.print(21, y, &name.name),
+            Some(ammo) => ctx.print(21, y, format!("{} ({})", &name.name, ammo.quantity)),
+        }
         equippable.push(entity);
-        y+= 1;
-        j += 1;
     }
 
     match ctx.key
@@ -175,23 +241,21 @@ pub fn remove_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Op
     let inventory = (&backpack, &names).join().filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(15, y-2, 31, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
     ctx.print_color(18, y-2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Remove Which Item?");
     ctx.print_color(18, y+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
 
     let mut equippable : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity )
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity ).enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
         ctx.set(18, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), 97+j as rltk::FontCharType);
         ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(21, y, &name.name);
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key 
@@ -216,8 +280,66 @@ pub fn remove_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Op
     }
 }
 
+// Every slot, in the order the equipment panel lists them
+const EQUIPMENT_SLOTS : [(EquipmentSlot, &str); 10] = [
+    (EquipmentSlot::Head, "Head"),
+    (EquipmentSlot::Amulet, "Amulet"),
+    (EquipmentSlot::Torso, "Torso"),
+    (EquipmentSlot::Hands, "Hands"),
+    (EquipmentSlot::Ring, "Ring"),
+    (EquipmentSlot::Legs, "Legs"),
+    (EquipmentSlot::Feet, "Feet"),
+    (EquipmentSlot::Melee, "Melee"),
+    (EquipmentSlot::Shield, "Shield"),
+    (EquipmentSlot::Ranged, "Ranged"),
+];
+
+pub fn show_equipment(gs : &mut State, ctx : &mut Rltk) -> ItemMenuResult
+{
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let two_handed = gs.ecs.read_storage::<TwoHanded>();
+    let entities = gs.ecs.entities();
+
+    let count = EQUIPMENT_SLOTS.len();
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(15, y-2, 40, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
+    ctx.print_color(18, y-2, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "Equipment");
+    ctx.print_color(18, y+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to close");
+
+    for (j, (slot, slot_name)) in EQUIPMENT_SLOTS.iter().enumerate()
+    {
+        let y = y + j as i32;
+        ctx.print_color(17, y, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), format!("{}:", slot_name));
+
+        let mut item_name = "-".to_string();
+        for (entity, equipped_by, name) in (&entities, &equipped, &names).join()
+        {
+            if equipped_by.owner != *player_entity { continue; }
+            if equipped_by.slot == *slot
+            {
+                item_name = name.name.clone();
+            }
+            // A two handed weapon fills the shield hand too
+            else if *slot == EquipmentSlot::Shield && equipped_by.slot == EquipmentSlot::Melee && two_handed.get(entity).is_some()
+            {
+                item_name = format!("({})", name.name);
+            }
+        }
+        ctx.print(25, y, &item_name);
+    }
+
+    match ctx.key
+    {
+        Some(VirtualKeyCode::Escape) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse,
+    }
+}
+
 pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuResult, Option<Point>)
 {
+    let (min_x, min_y, view_width, view_height) = camera::get_screen_bounds(&gs.ecs, ctx);
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_pos = gs.ecs.fetch::<Point>();
     let viewsheds = gs.ecs.read_storage::<Viewshed>();
@@ -233,9 +355,11 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
         for idx in visible.visible_tiles.iter() 
         {
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
-            if distance <= range as f32
+            let (screen_x, screen_y) = (idx.x - min_x, idx.y - min_y);
+            let on_screen = screen_x >= 0 && screen_x < view_width && screen_y >= 0 && screen_y < view_height;
+            if distance <= range as f32 && on_screen
             {
-                ctx.set_bg(idx.x, idx.y, RGB::named(rltk::BLUE));
+                ctx.set_bg(screen_x, screen_y, RGB::named(rltk::BLUE));
                 available_cells.push(idx);
             }
         }
@@ -247,20 +371,14 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
     
     // Draw mouse cursor
     let mouse_pos = ctx.mouse_pos();
-    let mut valid_target = false;
-    for idx in available_cells.iter()
-    {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1
-        {
-            valid_target = true;
-        }
-    }
-    if valid_target
+    let mouse_map_pos = camera::screen_to_map(&gs.ecs, ctx, mouse_pos.0, mouse_pos.1);
+    let valid_target = mouse_map_pos.filter(|pos| available_cells.contains(&pos));
+    if let Some(target) = valid_target
     {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
         if ctx.left_click
         {
-            return (ItemMenuResult::Selected, Some(Point::new(mouse_pos.0, mouse_pos.1)));
+            return (ItemMenuResult::Selected, Some(target));
         }
     }
     else
@@ -276,7 +394,7 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
 }
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum MainMenuSelection { NewGame, LoadGame, Quit }
+pub enum MainMenuSelection { NewGame, LoadGame, Seed, Quit }
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuResult { NoSelection{ selected : MainMenuSelection }, Selected{ selected: MainMenuSelection } }
@@ -311,13 +429,24 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult
             }
         }
 
+        let seed_text = format!("Seed: {}", gs.ecs.fetch::<RunSeed>().seed);
+        if selection == MainMenuSelection::Seed
+        {
+            ctx.print_color_centered(26, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), &seed_text);
+            ctx.print_color_centered(29, RGB::named(rltk::GREY), RGB::named(rltk::BLACK), "Type a number, BACKSPACE to erase, R for a random seed");
+        }
+        else
+        {
+            ctx.print_color_centered(26, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &seed_text);
+        }
+
         if selection == MainMenuSelection::Quit 
         {
-            ctx.print_color_centered(26, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Quit");
+            ctx.print_color_centered(27, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Quit");
         } 
         else 
         {
-            ctx.print_color_centered(26, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
+            ctx.print_color_centered(27, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
         }
 
         match ctx.key 
@@ -335,7 +464,8 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult
                         {
                             MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
                             MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                            MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame
+                            MainMenuSelection::Seed => newselection = MainMenuSelection::LoadGame,
+                            MainMenuSelection::Quit => newselection = MainMenuSelection::Seed
                         }
                         if newselection == MainMenuSelection::LoadGame && !save_exists
                         {
@@ -349,17 +479,25 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult
                         match selection 
                         {
                             MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
+                            MainMenuSelection::LoadGame => newselection = MainMenuSelection::Seed,
+                            MainMenuSelection::Seed => newselection = MainMenuSelection::Quit,
                             MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
                         }
                         if newselection == MainMenuSelection::LoadGame && !save_exists
                         {
-                            newselection = MainMenuSelection::Quit;
+                            newselection = MainMenuSelection::Seed;
                         }
                         return MainMenuResult::NoSelection{ selected: newselection }
                     }
                     VirtualKeyCode::Return => return MainMenuResult::Selected{ selected : selection },
-                    _ => return MainMenuResult::NoSelection{ selected: selection }
+                    _ =>
+                    {
+                        if selection == MainMenuSelection::Seed
+                        {
+                            edit_seed(&mut gs.ecs.write_resource::<RunSeed>(), key);
+                        }
+                        return MainMenuResult::NoSelection{ selected: selection }
+                    }
                 }
             }
         }
@@ -368,16 +506,56 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult
     MainMenuResult::NoSelection { selected: MainMenuSelection::NewGame }
 }
 
+// Lets the seed menu entry be typed into like a number field
+fn edit_seed(seed : &mut RunSeed, key : VirtualKeyCode)
+{
+    let digit = match key
+    {
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Some(0),
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Some(1),
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => Some(2),
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => Some(3),
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => Some(4),
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => Some(5),
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => Some(6),
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => Some(7),
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => Some(8),
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => Some(9),
+        _ => None
+    };
+
+    if let Some(digit) = digit
+    {
+        if let Some(new_seed) = seed.seed.checked_mul(10).and_then(|s| s.checked_add(digit))
+        {
+            seed.seed = new_seed;
+        }
+    }
+    else if key == VirtualKeyCode::Back
+    {
+        seed.seed /= 10;
+    }
+    else if key == VirtualKeyCode::R
+    {
+        *seed = RunSeed::random();
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult { NoSelection, QuitToMenu }
 
-pub fn game_over(ctx : &mut Rltk) -> GameOverResult 
+pub fn game_over(ecs : &World, ctx : &mut Rltk) -> GameOverResult 
 {
+    let seed = ecs.fetch::<RunSeed>();
+    let map = ecs.fetch::<Map>();
+    let run_details = format!("Seed {}, depth {}", seed.seed, map.depth);
+
     ctx.print_color_centered(15, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "You have died!");
     ctx.print_color_centered(17, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Score/results screen to be added");
     ctx.print_color_centered(18, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "In the future");
+    ctx.print_color_centered(19, RGB::named(rltk::GREY), RGB::named(rltk::BLACK), &run_details);
 
-    ctx.print_color_centered(20, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Press any key to return to the menu.");
+    ctx.print_color_centered(21, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Press any key to return to the menu.");
 
     match ctx.key 
     {
@@ -0,0 +1,74 @@
+use specs::prelude::*;
+use super::{HungerClock, HungerState, RunState, SufferDamage, gamelog::GameLog};
+
+// How many turns each hunger state lasts before sliding into the next one
+pub const WELL_FED_DURATION : i32 = 20;
+const NORMAL_DURATION : i32 = 200;
+const HUNGRY_DURATION : i32 = 200;
+
+pub struct HungerSystem {}
+
+impl<'a> System<'a> for HungerSystem
+{
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        WriteStorage<'a, HungerClock>,
+                        ReadExpect<'a, Entity>,
+                        ReadExpect<'a, RunState>,
+                        WriteStorage<'a, SufferDamage>,
+                        WriteExpect<'a, GameLog>
+                        );
+
+    fn run(&mut self, data : Self::SystemData)
+    {
+        let (entities, mut hunger_clock, player_entity, runstate, mut inflict_damage, mut log) = data;
+
+        if *runstate != RunState::PlayerTurn { return; }
+
+        for (entity, clock) in (&entities, &mut hunger_clock).join()
+        {
+            clock.duration -= 1;
+            if clock.duration > 0 { continue; }
+
+            match clock.state
+            {
+                HungerState::WellFed =>
+                {
+                    clock.state = HungerState::Normal;
+                    clock.duration = NORMAL_DURATION;
+                    if entity == *player_entity
+                    {
+                        log.entries.push("You are no longer well fed.".to_string());
+                    }
+                }
+                HungerState::Normal =>
+                {
+                    clock.state = HungerState::Hungry;
+                    clock.duration = HUNGRY_DURATION;
+                    if entity == *player_entity
+                    {
+                        log.entries.push("You are hungry.".to_string());
+                    }
+                }
+                HungerState::Hungry =>
+                {
+                    clock.state = HungerState::Starving;
+                    clock.duration = 0;
+                    if entity == *player_entity
+                    {
+                        log.entries.push("You are starving!".to_string());
+                    }
+                }
+                HungerState::Starving =>
+                {
+                    // Starvation hurts every turn until something gets eaten
+                    if entity == *player_entity
+                    {
+                        log.entries.push("Your hunger pangs are getting painful! You suffer 1 hp damage.".to_string());
+                    }
+                    SufferDamage::new_damage(&mut inflict_damage, entity, 1, None);
+                }
+            }
+        }
+    }
+}
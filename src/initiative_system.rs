@@ -0,0 +1,79 @@
+use specs::prelude::*;
+use super::{Initiative, MyTurn, Position, SpeedBonus, Equipped, RunState, StatusEffects, StatusKind,
+            status_system::has_status};
+
+// Everyone gains their speed in energy every tick, and gets to act once they have saved up
+// enough of it. Twice the normal speed means acting twice as often.
+pub const NORMAL_SPEED : i32 = 10;
+const ACTION_COST : i32 = 100;
+
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem
+{
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        WriteStorage<'a, Initiative>,
+                        ReadStorage<'a, Position>,
+                        WriteStorage<'a, MyTurn>,
+                        ReadStorage<'a, SpeedBonus>,
+                        ReadStorage<'a, Equipped>,
+                        ReadExpect<'a, Entity>,
+                        WriteExpect<'a, RunState>,
+                        ReadStorage<'a, StatusEffects>,
+                        );
+
+    fn run(&mut self, data : Self::SystemData)
+    {
+        let (entities, mut initiatives, positions, mut turns, speed_bonuses, equipped,
+             player_entity, mut runstate, statuses) = data;
+
+        if *runstate != RunState::Ticking { return; }
+
+        // Whoever acted last time is done
+        turns.clear();
+
+        // Work out how fast everyone on the level is right now
+        let mut speeds : Vec<(Entity, i32)> = Vec::new();
+        for (entity, initiative, _pos) in (&entities, &initiatives, &positions).join()
+        {
+            let mut speed = initiative.speed;
+            for (bonus, equipped_by) in (&speed_bonuses, &equipped).join()
+            {
+                if equipped_by.owner == entity
+                {
+                    speed += bonus.speed;
+                }
+            }
+            if has_status(statuses.get(entity), StatusKind::Slowed)
+            {
+                speed /= 2;
+            }
+            // Nobody is allowed to stop time
+            speeds.push((entity, i32::max(1, speed)));
+        }
+        if speeds.is_empty() { return; }
+
+        // Advance time until at least one entity is ready to act
+        let mut anyone_ready = false;
+        while !anyone_ready
+        {
+            for (entity, speed) in speeds.iter()
+            {
+                let initiative = initiatives.get_mut(*entity).unwrap();
+                initiative.energy += speed;
+                if initiative.energy >= ACTION_COST
+                {
+                    initiative.energy -= ACTION_COST;
+                    turns.insert(*entity, MyTurn{}).expect("Unable to insert turn");
+                    anyone_ready = true;
+
+                    if *entity == *player_entity
+                    {
+                        *runstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+        }
+    }
+}
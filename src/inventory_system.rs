@@ -1,8 +1,11 @@
 use specs::prelude::*;
-use super::{ WantsToPickupItem, Name, InBackpack, Position, gamelog::GameLog, Map,
+use super::{ WantsToPickupItem, Name, InBackpack, Position, gamelog::GameLog, Map, Ammunition,
             WantsToUseItem, WantsToRemoveItem, ProvidesHealing, InflictsDamage, 
             SufferDamage, CombatStats, 
-            Confusion, Consumable, AreaOfEffect, Equippable, Equipped };
+            InflictsStatus, StatusEffects, Consumable, AreaOfEffect, Equippable, Equipped,
+            ProvidesFood, HungerClock, HungerState, hunger_system::WELL_FED_DURATION,
+            Attributes, Skills, gamesystem::{attr_bonus, make_noise}, TwoHanded, EquipmentSlot,
+            status_system::{apply_status, status_name}, Alertness, Pursuing };
 
 pub struct ItemCollectionSystem {}
 
@@ -11,21 +14,43 @@ impl<'a> System<'a> for ItemCollectionSystem
     #[allow(clippy::type_complexity)]
     type SystemData = ( ReadExpect<'a, Entity>,
                         WriteExpect<'a, GameLog>,
+                        Entities<'a>,
                         WriteStorage<'a, WantsToPickupItem>,
                         WriteStorage<'a, Position>,
                         ReadStorage<'a, Name>,
-                        WriteStorage<'a, InBackpack>
+                        WriteStorage<'a, InBackpack>,
+                        WriteStorage<'a, Ammunition>
                         );
 
     fn run(&mut self, data : Self::SystemData)
     {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) = data;
+        let (player_entity, mut gamelog, entities, mut wants_pickup, mut positions, names, mut backpack,
+             mut ammunition) = data;
 
         for pickup in wants_pickup.join()
         {
             positions.remove(pickup.item);
-            backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by })
-                .expect("Unable to insert backpack entry");
+
+            // Ammunition joins a stack of the same type if there is one already
+            let mut stacked = false;
+            if let Some(picked_up) = ammunition.get(pickup.item).cloned()
+            {
+                let existing = (&entities, &ammunition, &backpack).join()
+                    .find(|(entity, ammo, carried)| *entity != pickup.item && carried.owner == pickup.collected_by
+                        && ammo.ammo_type == picked_up.ammo_type)
+                    .map(|(entity, _ammo, _carried)| entity);
+                if let Some(existing) = existing
+                {
+                    ammunition.get_mut(existing).unwrap().quantity += picked_up.quantity;
+                    entities.delete(pickup.item).expect("Unable to delete ammunition");
+                    stacked = true;
+                }
+            }
+            if !stacked
+            {
+                backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by })
+                    .expect("Unable to insert backpack entry");
+            }
             
             if pickup.collected_by == *player_entity 
             {
@@ -52,19 +77,32 @@ impl<'a> System<'a> for ItemUseSystem
                         ReadStorage<'a, ProvidesHealing>,
                         ReadStorage<'a, InflictsDamage>,
                         WriteStorage<'a, SufferDamage>,
-                        WriteStorage<'a, Confusion>,
+                        ReadStorage<'a, InflictsStatus>,
+                        WriteStorage<'a, StatusEffects>,
                         ReadStorage<'a, AreaOfEffect>,
                         WriteStorage<'a, CombatStats>,
                         ReadStorage<'a, Equippable>,
                         WriteStorage<'a, Equipped>,
-                        WriteStorage<'a, InBackpack>
+                        WriteStorage<'a, InBackpack>,
+                        ReadStorage<'a, ProvidesFood>,
+                        WriteStorage<'a, HungerClock>,
+                        ReadStorage<'a, Attributes>,
+                        ReadStorage<'a, Skills>,
+                        ReadStorage<'a, TwoHanded>,
+                        ReadStorage<'a, Position>,
+                        WriteStorage<'a, Alertness>,
+                        WriteStorage<'a, Pursuing>
                         );
 
     fn run(&mut self, data : Self::SystemData)
     {
         let (player_entity, mut gamelog, map, entities, mut wants_use, 
             names, consumables, healing, inflict_damage, mut suffer_damage, 
-            mut confused, aoe, mut combat_stats, equippable, mut equipped, mut backpack) = data;
+            inflicts_status, mut statuses, aoe, mut combat_stats, equippable, mut equipped, mut backpack,
+            provides_food, mut hunger_clocks, attributes, skills, two_handed, positions, mut alertness,
+            mut pursuing) = data;
+
+        let mut noises : Vec<rltk::Point> = Vec::new();
 
         for (entity, useitem) in (&entities, &wants_use).join()
         {
@@ -74,7 +112,7 @@ impl<'a> System<'a> for ItemUseSystem
             let mut targets : Vec<Entity> = Vec::new();
             match useitem.target 
             {
-                None => { targets.push( *player_entity ); }
+                None => { targets.push( entity ); }
                 Some(target) => 
                 {
                     let area_effect = aoe.get(useitem.item);
@@ -116,12 +154,19 @@ impl<'a> System<'a> for ItemUseSystem
                 {
                     let target_slot = can_equip.slot;
                     let target = targets[0];
+                    let is_two_handed = two_handed.get(useitem.item).is_some();
 
-                    // remove any items the target has in the item's slot
+                    // remove any items the target has in the item's slot. Two handed weapons also
+                    // need the shield hand free, and a shield can't go on next to one.
                     let mut to_unequip : Vec<Entity> = Vec::new();
                     for (item_entity, already_equipped, name) in (&entities, &equipped, &names).join()
                     {
-                        if already_equipped.owner == target && already_equipped.slot == target_slot
+                        if already_equipped.owner != target { continue; }
+
+                        let conflicts = already_equipped.slot == target_slot
+                            || (is_two_handed && already_equipped.slot == EquipmentSlot::Shield)
+                            || (target_slot == EquipmentSlot::Shield && two_handed.get(item_entity).is_some());
+                        if conflicts
                         {
                             to_unequip.push(item_entity);
                             if target == *player_entity
@@ -149,6 +194,22 @@ impl<'a> System<'a> for ItemUseSystem
             }
 
 
+            // eat food
+            if provides_food.get(useitem.item).is_some()
+            {
+                used_item = true;
+                let target = targets[0];
+                if let Some(hc) = hunger_clocks.get_mut(target)
+                {
+                    hc.state = HungerState::WellFed;
+                    hc.duration = WELL_FED_DURATION;
+                    if target == *player_entity
+                    {
+                        gamelog.entries.push(format!("You eat the {}.", names.get(useitem.item).unwrap().name));
+                    }
+                }
+            }
+
             // apply healing
             let item_heals = healing.get(useitem.item);
             match item_heals
@@ -167,6 +228,11 @@ impl<'a> System<'a> for ItemUseSystem
                                 gamelog.entries.push(format!("You use the {}, healing {} hp",
                                     names.get(useitem.item).unwrap().name, healer.heal_amount));
                             }
+                            else if let (Some(user_name), Some(target_name)) = (names.get(entity), names.get(*target))
+                            {
+                                gamelog.entries.push(format!("{} uses {} on {}, healing {} hp.",
+                                    user_name.name, names.get(useitem.item).unwrap().name, target_name.name, healer.heal_amount));
+                            }
                             used_item = true;
                         }
                     }
@@ -180,15 +246,34 @@ impl<'a> System<'a> for ItemUseSystem
                 None => {}
                 Some(damage) =>
                 {
+                    // Magic hits harder in clever, well trained hands
+                    let magic_bonus = attributes.get(entity).map_or(0, |a| attr_bonus(a.intelligence))
+                        + skills.get(entity).map_or(0, |s| s.magic);
+                    let damage_dealt = i32::max(1, damage.damage + magic_bonus);
+
+                    // Blasts are loud wherever they go off
+                    let blast_at = useitem.target
+                        .or_else(|| positions.get(entity).map(|pos| rltk::Point::new(pos.x, pos.y)));
+                    if let Some(blast_at) = blast_at
+                    {
+                        noises.push(blast_at);
+                    }
+
                     for mob in targets.iter()
                     {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage_dealt, Some(entity));
                         if entity == *player_entity
                         {
                             let mob_name = names.get(*mob).unwrap();
                             let item_name = names.get(useitem.item).unwrap();
                             gamelog.entries.push(format!("You use {} on {}, inflicting {} hp.", 
-                                item_name.name, mob_name.name, damage.damage));
+                                item_name.name, mob_name.name, damage_dealt));
+                        }
+                        else if let (Some(user_name), Some(mob_name)) = (names.get(entity), names.get(*mob))
+                        {
+                            let item_name = names.get(useitem.item).unwrap();
+                            gamelog.entries.push(format!("{} uses {} on {}, inflicting {} hp.",
+                                user_name.name, item_name.name, mob_name.name, damage_dealt));
                         }
 
                         used_item = true;
@@ -196,36 +281,34 @@ impl<'a> System<'a> for ItemUseSystem
                 }
             }
 
-            // apply Confusion
-            let mut add_confusion = Vec::new();
+            // apply status effects
+            if let Some(inflicts) = inflicts_status.get(useitem.item)
             {
-                let causes_confusion = confused.get(useitem.item);
-                match causes_confusion
+                let effect_names : Vec<String> = inflicts.effects.iter()
+                    .map(|e| status_name(e.kind).to_lowercase())
+                    .collect();
+                for mob in targets.iter()
                 {
-                    None => {}
-                    Some(confusion) =>
-                    {
-                        for mob in targets.iter()
-                        {
-                            add_confusion.push((*mob, confusion.turns ));
-                            if entity == *player_entity
-                            {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!("You use {} on {}, confusing them.", 
-                                    item_name.name, mob_name.name))
-                            }
+                    // Only living things can be poisoned or put to sleep
+                    if combat_stats.get(*mob).is_none() { continue; }
 
-                            used_item = true;
-                        }
+                    for effect in inflicts.effects.iter()
+                    {
+                        apply_status(&mut statuses, *mob, effect);
                     }
+                    if *mob == *player_entity
+                    {
+                        gamelog.entries.push(format!("You are {}.", effect_names.join(" and ")));
+                    }
+                    else if let Some(mob_name) = names.get(*mob)
+                    {
+                        gamelog.entries.push(format!("{} is {}.", mob_name.name, effect_names.join(" and ")));
+                    }
+
+                    used_item = true;
                 }
             }
-            for mob in add_confusion.iter()
-            {
-                confused.insert(mob.0, Confusion{ turns: mob.1 }).expect("Unable to insert status");
-            }
-            
+
             // consume item if necesssary
             if used_item 
             {
@@ -242,6 +325,11 @@ impl<'a> System<'a> for ItemUseSystem
         }
 
         wants_use.clear();
+
+        for noise in noises.iter()
+        {
+            make_noise(*noise, &positions, &mut alertness, &mut pursuing, &entities);
+        }
     }
 }
 
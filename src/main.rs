@@ -1,5 +1,5 @@
 ﻿extern crate serde;
-use rltk::{GameState, Rltk, Point};
+use rltk::{GameState, Rltk, Point, RandomNumberGenerator};
 use specs::prelude::*;
 use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
 
@@ -7,43 +7,66 @@ mod components;
 pub use components::*;
 mod map;
 pub use map::*;
+mod map_builders;
+mod camera;
+mod dungeon_master;
+pub use dungeon_master::DungeonMaster;
 mod player;
 use player::*;
 mod rect;
 pub use rect::Rect;
 mod visibility_system;
 pub use visibility_system::VisibilitySystem;
+mod initiative_system;
+pub use initiative_system::InitiativeSystem;
+mod status_system;
+pub use status_system::StatusEffectSystem;
+mod ai_map_system;
+pub use ai_map_system::{AiMapSystem, AiMaps};
 mod ai_system;
 pub use ai_system::MonsterAI;
 mod map_indexing_system;
 pub use map_indexing_system::MapIndexingSystem;
+mod trigger_system;
+pub use trigger_system::TriggerSystem;
+mod hunger_system;
+pub use hunger_system::HungerSystem;
 mod melee_combat_system;
 pub use melee_combat_system::MeleeCombatSystem;
+mod ranged_combat_system;
+pub use ranged_combat_system::RangedCombatSystem;
 mod damage_system;
 pub use damage_system::DamageSystem;
 mod inventory_system;
 pub use inventory_system::ItemCollectionSystem;
 pub use inventory_system::ItemUseSystem;
 pub use inventory_system::ItemRemoveSystem;
+mod gamesystem;
 mod gui;
 mod gamelog;
 mod spawner;
 mod saveload_system;
 pub mod random_table;
+pub mod raws;
+mod run_seed;
+pub use run_seed::{RunSeed, RngStream};
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum RunState { 
     AwaitingInput, 
     PreRun, 
     PlayerTurn, 
-    MonsterTurn, 
+    Ticking, 
     ShowInventory,
     ShowRemoveItem,
+    ShowEquipment,
     ShowTargeting { range : i32, item : Entity },
+    ShowFiring { range : i32 },
     MainMenu { menu_selection : gui::MainMenuSelection },
     SaveGame,
     QuitGame,
     NextLevel,
+    PreviousLevel,
     GameOver,
     }
 
@@ -56,18 +79,36 @@ impl State
 {
     fn run_systems(&mut self)
     {
+        let mut mapindex = MapIndexingSystem{};
+        mapindex.run_now(&self.ecs);
+
         let mut vis = VisibilitySystem{};
         vis.run_now(&self.ecs);
 
+        let mut initiative = InitiativeSystem{};
+        initiative.run_now(&self.ecs);
+
+        let mut status = StatusEffectSystem{};
+        status.run_now(&self.ecs);
+
+        let mut ai_maps = AiMapSystem{};
+        ai_maps.run_now(&self.ecs);
+
         let mut mob = MonsterAI{};
         mob.run_now(&self.ecs);
 
-        let mut mapindex = MapIndexingSystem{};
-        mapindex.run_now(&self.ecs);
+        let mut triggers = TriggerSystem{};
+        triggers.run_now(&self.ecs);
 
         let mut melee = MeleeCombatSystem{};
         melee.run_now(&self.ecs);
 
+        let mut ranged = RangedCombatSystem{};
+        ranged.run_now(&self.ecs);
+
+        let mut hunger = HungerSystem{};
+        hunger.run_now(&self.ecs);
+
         let mut damage = DamageSystem{};
         damage.run_now(&self.ecs);
 
@@ -83,107 +124,90 @@ impl State
         self.ecs.maintain();
     }
 
-    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity>
+    fn goto_next_level(&mut self)
     {
-        let entities = self.ecs.entities();
-        let player = self.ecs.read_storage::<Player>();
-        let backpack = self.ecs.read_storage::<InBackpack>();
-        let equipped = self.ecs.read_storage::<Equipped>();
-        let player_entity = self.ecs.fetch::<Entity>();
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        let new_level = self.goto_level(current_depth + 1);
 
-        let mut to_delete : Vec<Entity> = Vec::new();
-        for entity in entities.join()
+        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+        if !new_level
         {
-            // retain player
-            let p = player.get(entity);
-            if let Some(_p) = p
-            {
-                continue;
-            }
-
-            // retain player equipment
-            let bp = backpack.get(entity);
-            if let Some(bp) = bp
-            {
-                if bp.owner == *player_entity
-                {
-                    continue
-                }
-            }
-
-            let eq = equipped.get(entity);
-            if let Some(eq) = eq
-            {
-                if eq.owner == *player_entity
-                {
-                    continue
-                }
-            }
-
-            to_delete.push(entity);
+            gamelog.entries.push("You descend to the next level.".to_string());
+            return;
         }
 
-        to_delete
+        // Only fresh levels give the player a breather, or the stairs would be a free heal
+        let player_entity = self.ecs.fetch::<Entity>();
+        gamelog.entries.push("You descend to the next level, and take a moment to heal.".to_string());
+        let mut player_health_store = self.ecs.write_storage::<CombatStats>();
+        let player_health = player_health_store.get_mut(*player_entity);
+        if let Some(player_health) = player_health
+        {
+            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+        }
     }
 
-    fn goto_next_level(&mut self)
+    fn goto_previous_level(&mut self)
     {
-        let to_delete = self.entities_to_remove_on_level_change();
-        for target in to_delete
-        {
-            self.ecs.delete_entity(target).expect("Unable to delete entity");
-        }
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        self.goto_level(current_depth - 1);
 
-        // build new map and place the player
-        let worldmap;
+        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+        gamelog.entries.push("You ascend to the previous level.".to_string());
+    }
+
+    // Parks the current level in the dungeon master, then either restores the level at the new
+    // depth as it was left or builds it for the first time. Returns true if the level is new.
+    fn goto_level(&mut self, new_depth : i32) -> bool
+    {
         let current_depth;
         {
-            let mut worldmap_resource = self.ecs.write_resource::<Map>();
-            current_depth = worldmap_resource.depth;
-            *worldmap_resource = Map::new_map_rooms_and_corridors(current_depth + 1);
-            worldmap = worldmap_resource.clone();
+            let map = self.ecs.fetch::<Map>();
+            current_depth = map.depth;
+            let mut dungeon_master = self.ecs.write_resource::<DungeonMaster>();
+            dungeon_master.store_map(&map);
         }
+        dungeon_master::freeze_level_entities(&mut self.ecs);
 
-        // spawn enemies
-        for room in worldmap.rooms.iter().skip(1)
+        let stored_map = self.ecs.fetch::<DungeonMaster>().get_map(new_depth);
+        match stored_map
         {
-            spawner::spawn_room(&mut self.ecs, room, current_depth+1);
-        }
+            None =>
+            {
+                self.generate_world_map(new_depth);
+                true
+            }
+            Some(map) =>
+            {
+                // Arrive on the stairs that lead back to where we came from
+                let arrival_tile = if new_depth > current_depth { TileType::UpStairs } else { TileType::DownStairs };
+                let arrival_idx = map.tiles.iter().position(|tile| *tile == arrival_tile).unwrap_or(0) as i32;
+                let (arrival_x, arrival_y) = (arrival_idx % map.width, arrival_idx / map.width);
+                {
+                    let mut worldmap_resource = self.ecs.write_resource::<Map>();
+                    *worldmap_resource = map;
+                }
 
-        // place player and update resources
-        let (player_x, player_y) = worldmap.rooms[0].center();
-        let mut player_position = self.ecs.write_resource::<Point>();
-        *player_position = Point::new(player_x, player_y);
-        let mut position_components = self.ecs.write_storage::<Position>();
-        let player_entity = self.ecs.fetch::<Entity>();
-        let player_pos_comp = position_components.get_mut(*player_entity);
-        if let Some(player_pos_comp) = player_pos_comp
-        {
-            player_pos_comp.x = player_x;
-            player_pos_comp.y = player_y;
+                dungeon_master::thaw_level_entities(&mut self.ecs);
+                self.place_player(arrival_x, arrival_y);
+                false
+            }
         }
+    }
 
-        // mark the player's visibility as dirty
-        let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
-        let vs = viewshed_components.get_mut(*player_entity);
-        if let Some(vs) = vs
+    fn game_over_cleanup(&mut self)
+    {
+        // The next run gets a fresh seed, unless the player picks one from the menu
         {
-            vs.dirty = true;
+            let mut seed = self.ecs.write_resource::<RunSeed>();
+            *seed = RunSeed::random();
         }
 
-        // Notify the player and give them health
-        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
-        gamelog.entries.push("You descend to the next level, and take a moment to heal.".to_string());
-        let mut player_health_store = self.ecs.write_storage::<CombatStats>();
-        let player_health = player_health_store.get_mut(*player_entity);
-        if let Some(player_health) = player_health
-        {
-            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
-        }
+        self.new_game();
     }
 
-    // TODO: this should delete everything, then reset the level and call goto_next_level
-    fn game_over_cleanup(&mut self)
+    // Throws away the current run and starts a new one from the current run seed
+    fn new_game(&mut self)
     {
         // Delete everything
         let mut to_delete = Vec::new();
@@ -196,29 +220,66 @@ impl State
             self.ecs.delete_entity(*del).expect("Deletion failed");
         }
 
-        // build a new map and place the player
-        let worldmap;
         {
+            let mut dungeon_master = self.ecs.write_resource::<DungeonMaster>();
+            *dungeon_master = DungeonMaster::new();
+        }
+
+        // Spawn a new player, then build the first level around them
+        {
+            let player_entity = spawner::player(&mut self.ecs, 0, 0);
+            let mut player_entity_writer = self.ecs.write_resource::<Entity>();
+            *player_entity_writer = player_entity;
+        }
+
+        self.generate_world_map(1);
+    }
+
+    // Builds a new map for the given depth, spawns its contents and moves the player onto it
+    fn generate_world_map(&mut self, new_depth : i32)
+    {
+        let seed = *self.ecs.fetch::<RunSeed>();
+        let mut map_rng = seed.rng_for(RngStream::MapGeneration, new_depth);
+        let mut builder = map_builders::random_builder(new_depth, &mut map_rng);
+        builder.build_map(&mut map_rng);
+        let player_start = builder.get_starting_position();
+        {
+            let mut new_map = builder.get_map();
+
+            // Every level below the first has a way back up, right where the player arrives
+            if new_depth > 1
+            {
+                let start_idx = new_map.xy_idx(player_start.x, player_start.y);
+                new_map.tiles[start_idx] = TileType::UpStairs;
+            }
+
+            let mut dungeon_master = self.ecs.write_resource::<DungeonMaster>();
+            dungeon_master.store_map(&new_map);
             let mut worldmap_resource = self.ecs.write_resource::<Map>();
-            *worldmap_resource = Map::new_map_rooms_and_corridors(1);
-            worldmap = worldmap_resource.clone();
+            *worldmap_resource = new_map;
         }
 
-        // Spawn bad guys
-        for room in worldmap.rooms.iter().skip(1)
+        // Everything that happens on this level draws from its own stream, so a level can be
+        // reproduced from just the seed and the depth
         {
-            spawner::spawn_room(&mut self.ecs, room, 1);
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+            *rng = seed.rng_for(RngStream::World, new_depth);
         }
 
-        // Place the player and update resource
-        let (player_x, player_y) = worldmap.rooms[0].center();
-        let player_entity = spawner::player(&mut self.ecs, player_x, player_y);
+        // Spawn bad guys
+        builder.spawn_entities(&mut self.ecs);
+
+        self.place_player(player_start.x, player_start.y);
+    }
+
+    // Moves the player to a spot on the current map and updates everything that tracks them
+    fn place_player(&mut self, player_x : i32, player_y : i32)
+    {
         let mut player_position = self.ecs.write_resource::<Point>();
         *player_position = Point::new(player_x, player_y);
         let mut position_components = self.ecs.write_storage::<Position>();
-        let mut player_entity_writer = self.ecs.write_resource::<Entity>();
-        *player_entity_writer = player_entity;
-        let player_pos_comp = position_components.get_mut(player_entity);
+        let player_entity = self.ecs.fetch::<Entity>();
+        let player_pos_comp = position_components.get_mut(*player_entity);
         if let Some(player_pos_comp) = player_pos_comp
         {
             player_pos_comp.x = player_x;
@@ -227,7 +288,7 @@ impl State
 
         // Mark the player's visibility as dirty
         let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
-        let vs = viewshed_components.get_mut(player_entity);
+        let vs = viewshed_components.get_mut(*player_entity);
         if let Some(vs) = vs
         {
             vs.dirty = true;
@@ -252,26 +313,8 @@ impl GameState for State
             RunState::MainMenu{..} => {}
             _ =>
             {
-                draw_map(&self.ecs, ctx);
-
-                {
-                    let positions = self.ecs.read_storage::<Position>();
-                    let renderables = self.ecs.read_storage::<Renderable>();
-                    let map = self.ecs.fetch::<Map>();
-
-                    let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
-                    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order) );
-                    for (pos, render) in data.iter()
-                    {
-                        let idx = map.xy_idx(pos.x, pos.y);
-                        if map.visible_tiles[idx]
-                        {
-                            ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph);
-                        }
-                    }
-
-                    gui::draw_ui(&self.ecs, ctx);
-                }
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
             }
         }
 
@@ -287,20 +330,38 @@ impl GameState for State
             {
                 self.run_systems();
                 self.ecs.maintain();
-                // TODO: shouldn't this return true/false and let THIS function handle what comes next?
-                newrunstate = player_input(self, ctx);
+                if status_system::player_loses_turn(&self.ecs)
+                {
+                    newrunstate = RunState::PlayerTurn;
+                }
+                else
+                {
+                    // TODO: shouldn't this return true/false and let THIS function handle what comes next?
+                    newrunstate = player_input(self, ctx);
+                }
             }
             RunState::PlayerTurn =>
             {
                 self.run_systems();
                 self.ecs.maintain();
-                newrunstate = RunState::MonsterTurn;
+                newrunstate = RunState::Ticking;
             }
-            RunState::MonsterTurn =>
+            RunState::Ticking =>
             {
-                self.run_systems();
-                self.ecs.maintain();
-                newrunstate = RunState::AwaitingInput;
+                // Let everyone else act until it is the player's turn again. The dead are
+                // cleared out as we go, so they don't get to keep acting.
+                while newrunstate == RunState::Ticking
+                {
+                    self.run_systems();
+                    self.ecs.maintain();
+                    damage_system::delete_the_dead(&mut self.ecs);
+                    match *self.ecs.fetch::<RunState>()
+                    {
+                        RunState::AwaitingInput => newrunstate = RunState::AwaitingInput,
+                        RunState::GameOver => newrunstate = RunState::GameOver,
+                        _ => {}
+                    }
+                }
             }
             RunState::ShowInventory =>
             {
@@ -346,6 +407,13 @@ impl GameState for State
                     }
                 }
             }
+            RunState::ShowEquipment =>
+            {
+                if gui::show_equipment(self, ctx) == gui::ItemMenuResult::Cancel
+                {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
             RunState::ShowTargeting{range, item} =>
             {
                 let result = gui::ranged_target(self, ctx, range);
@@ -362,6 +430,22 @@ impl GameState for State
                     }
                 }
             }
+            RunState::ShowFiring{range} =>
+            {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0
+                {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected =>
+                    {
+                        let mut intent = self.ecs.write_storage::<WantsToShoot>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToShoot{ target: result.1.unwrap() })
+                                        .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
             RunState::MainMenu{..} =>
             {
                 let result = gui::main_menu(self, ctx);
@@ -373,13 +457,18 @@ impl GameState for State
                     {
                         match selected
                         {
-                            gui::MainMenuSelection::NewGame => newrunstate = RunState::PreRun,
+                            gui::MainMenuSelection::NewGame =>
+                            {
+                                self.new_game();
+                                newrunstate = RunState::PreRun;
+                            }
                             gui::MainMenuSelection::LoadGame => 
                             {
                                 saveload_system::load_game(&mut self.ecs);
                                 newrunstate = RunState::AwaitingInput;
                                 //saveload_system::delete_save();
                             }
+                            gui::MainMenuSelection::Seed => newrunstate = RunState::MainMenu{ menu_selection: selected },
                             gui::MainMenuSelection::Quit => { ::std::process::exit(0); }
                         }
                     }
@@ -399,9 +488,14 @@ impl GameState for State
                 self.goto_next_level();
                 newrunstate = RunState::PreRun;
             }
+            RunState::PreviousLevel =>
+            {
+                self.goto_previous_level();
+                newrunstate = RunState::PreRun;
+            }
             RunState::GameOver =>
             {
-                let result = gui::game_over(ctx);
+                let result = gui::game_over(&self.ecs, ctx);
                 match result
                 {
                     gui::GameOverResult::NoSelection => {}
@@ -429,6 +523,9 @@ impl GameState for State
 
 fn main() -> rltk::BError 
 {
+    // Check the seed before opening a window, so a mistyped one is reported straight away
+    let run_seed = RunSeed::from_args().unwrap_or_else(RunSeed::random);
+
     use rltk::RltkBuilder;
     let context = RltkBuilder::simple80x50()
         .with_title("Roguelike Rust")
@@ -455,34 +552,58 @@ fn main() -> rltk::BError
     gs.ecs.register::<Name>();
     gs.ecs.register::<BlocksTile>();
     gs.ecs.register::<CombatStats>();
+    gs.ecs.register::<Attributes>();
+    gs.ecs.register::<Skills>();
+    gs.ecs.register::<LootTable>();
+    gs.ecs.register::<Faction>();
+    gs.ecs.register::<Pursuing>();
+    gs.ecs.register::<Alertness>();
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<SufferDamage>();
-    gs.ecs.register::<Confusion>();
+    gs.ecs.register::<StatusEffects>();
+    gs.ecs.register::<InflictsStatus>();
     gs.ecs.register::<AreaOfEffect>();
+    gs.ecs.register::<Ability>();
     gs.ecs.register::<SimpleMarker<SerializeMe>>();
     gs.ecs.register::<SerializationHelper>();
     gs.ecs.register::<Equippable>();
     gs.ecs.register::<Equipped>();
-    gs.ecs.register::<MeleePowerBonus>();
+    gs.ecs.register::<TwoHanded>();
+    gs.ecs.register::<MeleeWeapon>();
+    gs.ecs.register::<NaturalAttack>();
+    gs.ecs.register::<RangedWeapon>();
+    gs.ecs.register::<Ammunition>();
+    gs.ecs.register::<WantsToShoot>();
     gs.ecs.register::<DefenseBonus>();
+    gs.ecs.register::<OtherLevelPosition>();
+    gs.ecs.register::<BlocksVisibility>();
+    gs.ecs.register::<Door>();
+    gs.ecs.register::<Hidden>();
+    gs.ecs.register::<EntryTrigger>();
+    gs.ecs.register::<SingleActivation>();
+    gs.ecs.register::<Teleports>();
+    gs.ecs.register::<EntityMoved>();
+    gs.ecs.register::<Experience>();
+    gs.ecs.register::<GivesExperience>();
+    gs.ecs.register::<HungerClock>();
+    gs.ecs.register::<ProvidesFood>();
+    gs.ecs.register::<SpeedBonus>();
+    gs.ecs.register::<Initiative>();
+    gs.ecs.register::<MyTurn>();
 
     gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
-    let map : Map = Map::new_map_rooms_and_corridors(1);
-    let(player_x, player_y) = map.rooms[0].center();
+    raws::load_raws();
 
-    let player_entity = spawner::player(&mut gs.ecs, player_x, player_y);
-    
-    gs.ecs.insert(rltk::RandomNumberGenerator::new());
-    for room in map.rooms.iter().skip(1)
-    {
-        spawner::spawn_room(&mut gs.ecs, room, 1);
-    }
-        
-    gs.ecs.insert( RunState::MainMenu{ menu_selection : gui::MainMenuSelection::NewGame } );
-    gs.ecs.insert(map);
-    gs.ecs.insert(Point::new(player_x, player_y));
+    gs.ecs.insert(Map::new(1, 64, 64));
+    gs.ecs.insert(DungeonMaster::new());
+    gs.ecs.insert(Point::new(0, 0));
+    gs.ecs.insert(AiMaps::default());
+    gs.ecs.insert(run_seed);
+    gs.ecs.insert(RandomNumberGenerator::new());
+    let player_entity = spawner::player(&mut gs.ecs, 0, 0);
     gs.ecs.insert(player_entity);
+    gs.ecs.insert( RunState::MainMenu{ menu_selection : gui::MainMenuSelection::NewGame } );
 
     gs.ecs.insert(gamelog::GameLog{entries : vec!["Welcome to Roguelike Rust".to_string()]});
 
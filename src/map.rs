@@ -1,29 +1,25 @@
-use rltk::{ RGB, Rltk, RandomNumberGenerator, BaseMap, Algorithm2D, Point };
-use super::{Rect};
-use std::cmp::{max, min};
+use rltk::{ BaseMap, Algorithm2D, Point };
 use specs::prelude::*;
 use serde::{Serialize, Deserialize};
-
-pub const MAPWIDTH : usize = 80;
-pub const MAPHEIGHT : usize = 43;
-pub const MAPCOUNT : usize = MAPHEIGHT * MAPWIDTH;
+use super::{Door, BlocksTile, BlocksVisibility, Renderable};
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType
 {
-    Wall, Floor, DownStairs
+    Wall, Floor, DownStairs, UpStairs
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map 
 {
     pub tiles : Vec<TileType>,
-    pub rooms : Vec<Rect>,
     pub width : i32,
     pub height : i32,
     pub revealed_tiles : Vec<bool>,
     pub visible_tiles : Vec<bool>,
     pub blocked : Vec<bool>,
+    pub view_blocked : Vec<bool>,
+    pub closed_door : Vec<bool>,
     pub depth : i32,
 
     #[serde(skip_serializing)]
@@ -39,12 +35,13 @@ impl Map
         (y as usize * self.width as usize) + x as usize
     }
 
-    fn is_exit_valid(&self, x:i32, y:i32) -> bool 
+    pub fn is_exit_valid(&self, x:i32, y:i32) -> bool 
     {
         if x < 1 || x > self.width-1 || y < 1 || y > self.height-1 { return false; }
         let idx = self.xy_idx(x, y);
 
-        !self.blocked[idx]
+        // Closed doors block movement, but anyone can open them, so paths may run through them
+        !self.blocked[idx] || self.closed_door[idx]
     }
     
     pub fn populate_blocked(&mut self) 
@@ -55,42 +52,6 @@ impl Map
         }
     }
     
-    fn apply_room_to_map(&mut self, room : &Rect)
-    {
-        for y in room.y1 + 1 ..= room.y2
-        {
-            for x in room.x1 + 1 ..= room.x2
-            {
-                let idx = self.xy_idx(x, y);
-                self.tiles[idx] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_horizontal_tunnel(&mut self, x1:i32, x2:i32, y:i32)
-    {
-        for x in min(x1, x2) ..= max(x1, x2)
-        {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < self.width as usize * self.height as usize
-            {
-                self.tiles[idx] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_vertical_tunnel(&mut self, y1:i32, y2:i32, x:i32)
-    {
-        for y in min(y1, y2) ..= max(y1, y2)
-        {
-            let idx = self.xy_idx(x, y);
-            if idx > 0 && idx < self.width as usize * self.height as usize
-            {
-                self.tiles[idx] = TileType::Floor;
-            }
-        }
-    }
-
     pub fn clear_content_index(&mut self) 
     {
         for content in self.tile_content.iter_mut()
@@ -99,69 +60,45 @@ impl Map
         }
     }
 
-    pub fn new_map_rooms_and_corridors(new_depth : i32) -> Map 
+    // Generates an empty map, consisting entirely of solid walls
+    pub fn new(new_depth : i32, width : i32, height : i32) -> Map
     {
-        let mut map = Map
+        let map_tile_count = (width * height) as usize;
+        Map
         {
-            tiles : vec![TileType::Wall; MAPCOUNT],
-            rooms : Vec::new(),
-            width : MAPWIDTH as i32,
-            height: MAPHEIGHT as i32,
-            revealed_tiles : vec![false; MAPCOUNT],
-            visible_tiles : vec![false; MAPCOUNT],
-            blocked : vec![false; MAPCOUNT],
-            tile_content : vec![Vec::new(); MAPCOUNT],
+            tiles : vec![TileType::Wall; map_tile_count],
+            width,
+            height,
+            revealed_tiles : vec![false; map_tile_count],
+            visible_tiles : vec![false; map_tile_count],
+            blocked : vec![false; map_tile_count],
+            view_blocked : vec![false; map_tile_count],
+            closed_door : vec![false; map_tile_count],
+            tile_content : vec![Vec::new(); map_tile_count],
             depth: new_depth,
-        };
-
-        const MAX_ROOMS : i32 = 30;
-        const MIN_SIZE : i32 = 6;
-        const MAX_SIZE : i32 = 10;
-
-        let mut rng = RandomNumberGenerator::new();
-
-        for _ in 0..MAX_ROOMS
-        {
-            let w = rng.range(MIN_SIZE, MAX_SIZE);
-            let h = rng.range(MIN_SIZE, MAX_SIZE);
-            let x = rng.roll_dice(1, map.width - w - 1) - 1;
-            let y = rng.roll_dice(1, map.height - h - 1) - 1;
-            let new_room = Rect::new(x, y, w, h);
-            let mut ok = true;
-            for other_room in map.rooms.iter()
-            {
-                if new_room.intersect(other_room) { ok = false }
-            }
-            if ok 
-            {
-                map.apply_room_to_map(&new_room);
-
-                if !map.rooms.is_empty() 
-                {
-                    let (new_x, new_y) = new_room.center();
-                    let (prev_x, prev_y) = map.rooms[map.rooms.len()-1].center();
-                    if rng.range(0,2) == 1
-                    {
-                        map.apply_horizontal_tunnel(prev_x, new_x, prev_y);
-                        map.apply_vertical_tunnel(prev_y, new_y, new_x);
-                    } 
-                    else 
-                    {
-                        map.apply_vertical_tunnel(prev_y, new_y, prev_x);
-                        map.apply_horizontal_tunnel(prev_x, new_x, new_y);
-                    }
-                }
-
-                map.rooms.push(new_room);
-            }
         }
+    }
+}
 
-        let stairs_position = map.rooms[map.rooms.len()-1].center();
-        let stairs_idx = map.xy_idx(stairs_position.0, stairs_position.1);
-        map.tiles[stairs_idx] = TileType::DownStairs;
-
-        map
+// Swings a closed door open. The map is updated straight away, so the doorway can be walked
+// and seen through without waiting for the map to be indexed again.
+pub fn open_door(door_entity : Entity, idx : usize, map : &mut Map, doors : &mut WriteStorage<Door>,
+                 blocks_tile : &mut WriteStorage<BlocksTile>, blocks_visibility : &mut WriteStorage<BlocksVisibility>,
+                 renderables : &mut WriteStorage<Renderable>)
+{
+    if let Some(door) = doors.get_mut(door_entity)
+    {
+        door.open = true;
     }
+    blocks_tile.remove(door_entity);
+    blocks_visibility.remove(door_entity);
+    if let Some(render) = renderables.get_mut(door_entity)
+    {
+        render.glyph = rltk::to_cp437('/');
+    }
+    map.blocked[idx] = false;
+    map.view_blocked[idx] = false;
+    map.closed_door[idx] = false;
 }
 
 impl Algorithm2D for Map
@@ -176,7 +113,7 @@ impl BaseMap for Map
 {
     fn is_opaque(&self, idx:usize) -> bool 
     {
-        self.tiles[idx as usize] == TileType::Wall
+        self.tiles[idx] == TileType::Wall || self.view_blocked[idx]
     }
 
     fn get_pathing_distance(&self, idx1:usize, idx2:usize) -> f32
@@ -209,48 +146,3 @@ impl BaseMap for Map
         exits
     }
 }
-
-pub fn draw_map(ecs: &World, ctx : &mut Rltk) 
-{
-    let map = ecs.fetch::<Map>();
-
-    let mut y = 0;
-    let mut x = 0;
-    for (idx, tile) in map.tiles.iter().enumerate()
-    {
-        // Render a tile depending on the tile TileType
-        if map.revealed_tiles[idx]
-        {
-            let glyph;
-            let mut fg;
-            match tile 
-            {
-                TileType::Floor => {
-                    glyph = rltk::to_cp437('.');
-                    fg = RGB::from_f32(0.0, 0.5, 0.5);
-                }
-                TileType::Wall => {
-                    glyph =  rltk::to_cp437('#');
-                    fg = RGB::from_f32(0.0, 1.0, 0.0);
-                }
-                TileType::DownStairs => {
-                    glyph = rltk::to_cp437('>');
-                    fg = RGB::from_f32(0.0, 1.0, 1.0);
-                }
-            }
-            if !map.visible_tiles[idx]
-            {
-                fg = fg.to_greyscale();
-            }
-            ctx.set(x, y, fg, RGB::from_f32(0., 0., 0.), glyph);
-        }
-
-        // Move the coordinates
-        x += 1;
-        if x > MAPWIDTH as i32-1 
-        {
-            x = 0;
-            y += 1;
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,156 @@
+use super::{MapBuilder, Map, Rect, TileType, Position, spawner,
+            apply_room_to_map, apply_horizontal_tunnel, apply_vertical_tunnel, add_doors};
+use rltk::RandomNumberGenerator;
+
+// Leaves smaller than this (in either direction) are never split further
+const MIN_LEAF_SIZE : i32 = 8;
+// Leaves larger than this are always split
+const MAX_LEAF_SIZE : i32 = 20;
+const MIN_ROOM_SIZE : i32 = 4;
+
+pub struct BspDungeonBuilder
+{
+    map : Map,
+    starting_position : Position,
+    depth : i32,
+    rooms : Vec<Rect>,
+    spawn_list : Vec<(usize, String)>,
+}
+
+impl MapBuilder for BspDungeonBuilder
+{
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        self.build(rng);
+    }
+
+    fn get_map(&self) -> Map
+    {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position
+    {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>
+    {
+        &self.spawn_list
+    }
+}
+
+impl BspDungeonBuilder
+{
+    pub fn new(new_depth : i32, width : i32, height : i32) -> BspDungeonBuilder
+    {
+        BspDungeonBuilder
+        {
+            map : Map::new(new_depth, width, height),
+            starting_position : Position{ x: 0, y: 0 },
+            depth : new_depth,
+            rooms : Vec::new(),
+            spawn_list : Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        // Leave a one tile border of wall around the whole map
+        let whole_map = Rect::new(1, 1, self.map.width - 2, self.map.height - 2);
+        self.partition(whole_map, rng);
+
+        let stairs_position = self.rooms[self.rooms.len()-1].center();
+        let stairs_idx = self.map.xy_idx(stairs_position.0, stairs_position.1);
+        self.map.tiles[stairs_idx] = TileType::DownStairs;
+
+        let start_pos = self.rooms[0].center();
+        self.starting_position = Position{ x: start_pos.0, y: start_pos.1 };
+
+        add_doors(&self.map, &self.rooms, &mut self.spawn_list);
+
+        // Spawn some entities
+        for room in self.rooms.iter().skip(1)
+        {
+            spawner::spawn_room(&self.map, rng, room, self.depth, &mut self.spawn_list);
+        }
+    }
+
+    // Recursively splits a leaf in two until it is too small to split, then places a room in it.
+    // Returns one of the rooms placed beneath this leaf, so the parent can connect its two halves.
+    fn partition(&mut self, leaf : Rect, rng : &mut RandomNumberGenerator) -> Rect
+    {
+        let width = leaf.x2 - leaf.x1;
+        let height = leaf.y2 - leaf.y1;
+        let can_split_x = width >= MIN_LEAF_SIZE * 2;
+        let can_split_y = height >= MIN_LEAF_SIZE * 2;
+        let must_split = width > MAX_LEAF_SIZE || height > MAX_LEAF_SIZE;
+
+        if !(can_split_x || can_split_y) || (!must_split && rng.roll_dice(1, 4) == 1)
+        {
+            return self.place_room(leaf, rng);
+        }
+
+        // Prefer to split across the longest side, so leaves stay roughly square
+        let split_x = if can_split_x && can_split_y
+        {
+            if width as f32 > height as f32 * 1.25 { true }
+            else if height as f32 > width as f32 * 1.25 { false }
+            else { rng.range(0, 2) == 0 }
+        }
+        else
+        {
+            can_split_x
+        };
+
+        let (first, second) = if split_x
+        {
+            let split = rng.range(MIN_LEAF_SIZE, width - MIN_LEAF_SIZE + 1);
+            (Rect::new(leaf.x1, leaf.y1, split, height), Rect::new(leaf.x1 + split, leaf.y1, width - split, height))
+        }
+        else
+        {
+            let split = rng.range(MIN_LEAF_SIZE, height - MIN_LEAF_SIZE + 1);
+            (Rect::new(leaf.x1, leaf.y1, width, split), Rect::new(leaf.x1, leaf.y1 + split, width, height - split))
+        };
+
+        let first_room = self.partition(first, rng);
+        let second_room = self.partition(second, rng);
+        self.connect_rooms(&first_room, &second_room, rng);
+
+        if rng.range(0, 2) == 0 { first_room } else { second_room }
+    }
+
+    // Places a randomly sized room inside a leaf. The room never touches the leaf's right or bottom
+    // edge, which guarantees a wall between neighbouring leaves.
+    fn place_room(&mut self, leaf : Rect, rng : &mut RandomNumberGenerator) -> Rect
+    {
+        let leaf_width = leaf.x2 - leaf.x1;
+        let leaf_height = leaf.y2 - leaf.y1;
+        let w = rng.range(MIN_ROOM_SIZE, leaf_width);
+        let h = rng.range(MIN_ROOM_SIZE, leaf_height);
+        let x = leaf.x1 + rng.range(0, leaf_width - w);
+        let y = leaf.y1 + rng.range(0, leaf_height - h);
+
+        let room = Rect::new(x, y, w, h);
+        apply_room_to_map(&mut self.map, &room);
+        self.rooms.push(room);
+        room
+    }
+
+    fn connect_rooms(&mut self, first : &Rect, second : &Rect, rng : &mut RandomNumberGenerator)
+    {
+        let (first_x, first_y) = first.center();
+        let (second_x, second_y) = second.center();
+        if rng.range(0, 2) == 1
+        {
+            apply_horizontal_tunnel(&mut self.map, first_x, second_x, first_y);
+            apply_vertical_tunnel(&mut self.map, first_y, second_y, second_x);
+        }
+        else
+        {
+            apply_vertical_tunnel(&mut self.map, first_y, second_y, first_x);
+            apply_horizontal_tunnel(&mut self.map, first_x, second_x, second_y);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use super::{MapBuilder, Map, TileType, Position, spawner,
+            remove_unreachable_areas_returning_most_distant, generate_voronoi_spawn_regions};
+use rltk::RandomNumberGenerator;
+
+const SMOOTHING_ITERATIONS : i32 = 15;
+
+pub struct CellularAutomataBuilder
+{
+    map : Map,
+    starting_position : Position,
+    depth : i32,
+    spawn_list : Vec<(usize, String)>,
+}
+
+impl MapBuilder for CellularAutomataBuilder
+{
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        self.build(rng);
+    }
+
+    fn get_map(&self) -> Map
+    {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position
+    {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>
+    {
+        &self.spawn_list
+    }
+}
+
+impl CellularAutomataBuilder
+{
+    pub fn new(new_depth : i32, width : i32, height : i32) -> CellularAutomataBuilder
+    {
+        CellularAutomataBuilder
+        {
+            map : Map::new(new_depth, width, height),
+            starting_position : Position{ x: 0, y: 0 },
+            depth : new_depth,
+            spawn_list : Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        // Start with random noise: roughly 55% floor, 45% wall
+        for y in 1 .. self.map.height - 1
+        {
+            for x in 1 .. self.map.width - 1
+            {
+                let idx = self.map.xy_idx(x, y);
+                if rng.roll_dice(1, 100) > 55
+                {
+                    self.map.tiles[idx] = TileType::Floor;
+                }
+                else
+                {
+                    self.map.tiles[idx] = TileType::Wall;
+                }
+            }
+        }
+
+        // Smooth the noise into caves. A tile becomes wall if it is crowded by walls
+        // or completely isolated, and floor otherwise.
+        for _i in 0 .. SMOOTHING_ITERATIONS
+        {
+            let mut newtiles = self.map.tiles.clone();
+
+            for y in 1 .. self.map.height - 1
+            {
+                for x in 1 .. self.map.width - 1
+                {
+                    let neighbors = self.count_wall_neighbors(x, y);
+                    let idx = self.map.xy_idx(x, y);
+                    if neighbors > 4 || neighbors == 0
+                    {
+                        newtiles[idx] = TileType::Wall;
+                    }
+                    else
+                    {
+                        newtiles[idx] = TileType::Floor;
+                    }
+                }
+            }
+
+            self.map.tiles = newtiles;
+        }
+
+        // Find a starting point: start at the middle and walk left until we find an open tile
+        self.starting_position = Position{ x: self.map.width / 2, y: self.map.height / 2 };
+        let mut start_idx = self.map.xy_idx(self.starting_position.x, self.starting_position.y);
+        while self.map.tiles[start_idx] != TileType::Floor
+        {
+            self.starting_position.x -= 1;
+            if self.starting_position.x < 1
+            {
+                // Ran out of row, so try the next one down
+                self.starting_position.x = self.map.width - 2;
+                self.starting_position.y += 1;
+            }
+            start_idx = self.map.xy_idx(self.starting_position.x, self.starting_position.y);
+        }
+
+        // Cull the caves we can't reach, and put the stairs as far away as possible
+        let exit_tile = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_tile] = TileType::DownStairs;
+
+        // Spawn some entities, keeping them off the player's starting tile
+        let spawn_regions = generate_voronoi_spawn_regions(&self.map, rng);
+        for region in spawn_regions.iter()
+        {
+            let area : Vec<usize> = region.iter().filter(|idx| **idx != start_idx).copied().collect();
+            spawner::spawn_region(rng, &area, self.depth, &mut self.spawn_list);
+        }
+    }
+
+    fn count_wall_neighbors(&self, x : i32, y : i32) -> i32
+    {
+        let mut neighbors = 0;
+        for dy in -1 ..= 1
+        {
+            for dx in -1 ..= 1
+            {
+                if (dx != 0 || dy != 0) && self.map.tiles[self.map.xy_idx(x + dx, y + dy)] == TileType::Wall
+                {
+                    neighbors += 1;
+                }
+            }
+        }
+        neighbors
+    }
+}
@@ -0,0 +1,160 @@
+use super::{Map, Rect, TileType};
+use rltk::{RandomNumberGenerator, Point};
+use std::cmp::{max, min};
+
+pub fn apply_room_to_map(map : &mut Map, room : &Rect)
+{
+    for y in room.y1 + 1 ..= room.y2
+    {
+        for x in room.x1 + 1 ..= room.x2
+        {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_horizontal_tunnel(map : &mut Map, x1:i32, x2:i32, y:i32)
+{
+    for x in min(x1, x2) ..= max(x1, x2)
+    {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize
+        {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_vertical_tunnel(map : &mut Map, y1:i32, y2:i32, x:i32)
+{
+    for y in min(y1, y2) ..= max(y1, y2)
+    {
+        let idx = map.xy_idx(x, y);
+        if idx > 0 && idx < map.width as usize * map.height as usize
+        {
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
+
+// Finds the gaps that corridors punch into each room's surrounding wall, and puts a door in each
+// one that is a single tile wide
+pub fn add_doors(map : &Map, rooms : &[Rect], spawn_list : &mut Vec<(usize, String)>)
+{
+    let mut door_tiles : Vec<usize> = Vec::new();
+    for room in rooms.iter()
+    {
+        for x in room.x1 + 1 ..= room.x2
+        {
+            add_door_if_possible(map, x, room.y1, &mut door_tiles);
+            add_door_if_possible(map, x, room.y2 + 1, &mut door_tiles);
+        }
+        for y in room.y1 + 1 ..= room.y2
+        {
+            add_door_if_possible(map, room.x1, y, &mut door_tiles);
+            add_door_if_possible(map, room.x2 + 1, y, &mut door_tiles);
+        }
+    }
+
+    for idx in door_tiles.iter()
+    {
+        spawn_list.push((*idx, "Door".to_string()));
+    }
+}
+
+fn add_door_if_possible(map : &Map, x : i32, y : i32, door_tiles : &mut Vec<usize>)
+{
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 { return; }
+
+    let idx = map.xy_idx(x, y);
+    if map.tiles[idx] != TileType::Floor || door_tiles.contains(&idx) { return; }
+
+    // Short corridors between two rooms would otherwise get a door at each end, side by side
+    let w = map.width as usize;
+    if door_tiles.contains(&(idx - 1)) || door_tiles.contains(&(idx + 1))
+        || door_tiles.contains(&(idx - w)) || door_tiles.contains(&(idx + w)) { return; }
+
+    // A doorway needs wall on both sides of it, either left and right or above and below
+    let is_wall = |x, y| map.tiles[map.xy_idx(x, y)] == TileType::Wall;
+    if (is_wall(x - 1, y) && is_wall(x + 1, y)) || (is_wall(x, y - 1) && is_wall(x, y + 1))
+    {
+        door_tiles.push(idx);
+    }
+}
+
+// Walls off every tile that cannot be reached from the start, and returns the index of the
+// reachable tile that is farthest away from it.
+pub fn remove_unreachable_areas_returning_most_distant(map : &mut Map, start_idx : usize) -> usize
+{
+    map.populate_blocked();
+    let map_starts : Vec<usize> = vec![start_idx];
+    let dijkstra_map = rltk::DijkstraMap::new(map.width as usize, map.height as usize, &map_starts, &*map, (map.width * map.height) as f32);
+    let mut exit_tile = (0, 0.0f32);
+    for (i, tile) in map.tiles.iter_mut().enumerate()
+    {
+        if *tile == TileType::Floor
+        {
+            let distance_to_start = dijkstra_map.map[i];
+            if distance_to_start == f32::MAX
+            {
+                // We can't get to this tile, so wall it off
+                *tile = TileType::Wall;
+            }
+            else if distance_to_start > exit_tile.1
+            {
+                // If it is further away than our current exit candidate, move the exit
+                exit_tile.0 = i;
+                exit_tile.1 = distance_to_start;
+            }
+        }
+    }
+
+    exit_tile.0
+}
+
+// Splits the map's floor into regions by nearest seed point (a Voronoi diagram), so maps without
+// rooms still have distinct areas to spawn entities into.
+pub fn generate_voronoi_spawn_regions(map : &Map, rng : &mut RandomNumberGenerator) -> Vec<Vec<usize>>
+{
+    // 32 regions for a screen-sized map, and proportionally more for bigger ones
+    let n_seeds = usize::max(32, (32 * map.width * map.height / (80 * 43)) as usize);
+
+    let mut seeds : Vec<Point> = Vec::new();
+    while seeds.len() < n_seeds
+    {
+        let x = rng.roll_dice(1, map.width - 1);
+        let y = rng.roll_dice(1, map.height - 1);
+        let candidate = Point::new(x, y);
+        if !seeds.contains(&candidate)
+        {
+            seeds.push(candidate);
+        }
+    }
+
+    let mut regions : Vec<Vec<usize>> = vec![Vec::new(); n_seeds];
+    for y in 1 .. map.height - 1
+    {
+        for x in 1 .. map.width - 1
+        {
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Floor
+            {
+                let here = Point::new(x, y);
+                let mut nearest = (0, f32::MAX);
+                for (i, seed) in seeds.iter().enumerate()
+                {
+                    let distance = rltk::DistanceAlg::PythagorasSquared.distance2d(here, *seed);
+                    if distance < nearest.1
+                    {
+                        nearest = (i, distance);
+                    }
+                }
+                regions[nearest.0].push(idx);
+            }
+        }
+    }
+
+    regions.retain(|region| !region.is_empty());
+    regions
+}
@@ -0,0 +1,220 @@
+use super::{MapBuilder, Map, TileType, Position, spawner,
+            remove_unreachable_areas_returning_most_distant, generate_voronoi_spawn_regions};
+use rltk::RandomNumberGenerator;
+
+// How far outside the existing floor inward-walking particles start, and how long they may
+// stumble around before giving up
+const SPAWN_MARGIN : i32 = 10;
+const MAX_STUMBLES : i32 = 400;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DLAAlgorithm { WalkInwards, WalkOutwards, CentralAttractor }
+
+pub struct DLABuilder
+{
+    map : Map,
+    starting_position : Position,
+    depth : i32,
+    spawn_list : Vec<(usize, String)>,
+    algorithm : DLAAlgorithm,
+    brush_size : i32,
+    floor_percent : f32,
+}
+
+impl MapBuilder for DLABuilder
+{
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        self.build(rng);
+    }
+
+    fn get_map(&self) -> Map
+    {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position
+    {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>
+    {
+        &self.spawn_list
+    }
+}
+
+impl DLABuilder
+{
+    pub fn new(new_depth : i32, width : i32, height : i32, algorithm : DLAAlgorithm, brush_size : i32, floor_percent : f32) -> DLABuilder
+    {
+        DLABuilder
+        {
+            map : Map::new(new_depth, width, height),
+            starting_position : Position{ x: 0, y: 0 },
+            depth : new_depth,
+            spawn_list : Vec::new(),
+            algorithm,
+            brush_size,
+            floor_percent,
+        }
+    }
+
+    // Particles start at random points and stumble around until they bump into the existing floor
+    pub fn walk_inwards(new_depth : i32, width : i32, height : i32) -> DLABuilder
+    {
+        DLABuilder::new(new_depth, width, height, DLAAlgorithm::WalkInwards, 1, 0.25)
+    }
+
+    // Particles start in the middle and stumble outwards until they hit a wall
+    pub fn walk_outwards(new_depth : i32, width : i32, height : i32) -> DLABuilder
+    {
+        DLABuilder::new(new_depth, width, height, DLAAlgorithm::WalkOutwards, 2, 0.25)
+    }
+
+    // Particles travel in a straight line towards the middle, giving a branching starburst
+    pub fn central_attractor(new_depth : i32, width : i32, height : i32) -> DLABuilder
+    {
+        DLABuilder::new(new_depth, width, height, DLAAlgorithm::CentralAttractor, 2, 0.25)
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        // Carve a starting seed
+        self.starting_position = Position{ x: self.map.width / 2, y: self.map.height / 2 };
+        let start_idx = self.map.xy_idx(self.starting_position.x, self.starting_position.y);
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)].iter()
+        {
+            let idx = self.map.xy_idx(self.starting_position.x + dx, self.starting_position.y + dy);
+            self.map.tiles[idx] = TileType::Floor;
+        }
+
+        let total_tiles = self.map.width * self.map.height;
+        let desired_floor_tiles = (self.floor_percent * total_tiles as f32) as usize;
+        let mut floor_tile_count = self.count_floor_tiles();
+        // The smallest box (x1, x2, y1, y2) holding all the floor so far
+        let mut bounds = (self.starting_position.x - 1, self.starting_position.x + 1,
+                          self.starting_position.y - 1, self.starting_position.y + 1);
+        while floor_tile_count < desired_floor_tiles
+        {
+            match self.algorithm
+            {
+                DLAAlgorithm::WalkInwards =>
+                {
+                    // Start particles a little way outside the floor carved so far, and give up on any
+                    // that wander off; on big maps a particle dropped just anywhere can stumble around
+                    // for a very long time before it hits anything.
+                    let mut digger_x = rng.range(i32::max(2, bounds.0 - SPAWN_MARGIN), i32::min(self.map.width - 2, bounds.1 + SPAWN_MARGIN) + 1);
+                    let mut digger_y = rng.range(i32::max(2, bounds.2 - SPAWN_MARGIN), i32::min(self.map.height - 2, bounds.3 + SPAWN_MARGIN) + 1);
+                    let mut prev_x = digger_x;
+                    let mut prev_y = digger_y;
+                    let mut digger_idx = self.map.xy_idx(digger_x, digger_y);
+                    let mut stumbles = 0;
+                    while self.map.tiles[digger_idx] == TileType::Wall && stumbles < MAX_STUMBLES
+                    {
+                        prev_x = digger_x;
+                        prev_y = digger_y;
+                        self.stumble(&mut digger_x, &mut digger_y, rng);
+                        digger_idx = self.map.xy_idx(digger_x, digger_y);
+                        stumbles += 1;
+                    }
+                    if self.map.tiles[digger_idx] == TileType::Floor
+                    {
+                        floor_tile_count += self.paint(prev_x, prev_y);
+                        bounds = (i32::min(bounds.0, prev_x), i32::max(bounds.1, prev_x),
+                                  i32::min(bounds.2, prev_y), i32::max(bounds.3, prev_y));
+                    }
+                }
+                DLAAlgorithm::WalkOutwards =>
+                {
+                    let mut digger_x = self.starting_position.x;
+                    let mut digger_y = self.starting_position.y;
+                    let mut digger_idx = self.map.xy_idx(digger_x, digger_y);
+                    while self.map.tiles[digger_idx] == TileType::Floor
+                    {
+                        self.stumble(&mut digger_x, &mut digger_y, rng);
+                        digger_idx = self.map.xy_idx(digger_x, digger_y);
+                    }
+                    floor_tile_count += self.paint(digger_x, digger_y);
+                }
+                DLAAlgorithm::CentralAttractor =>
+                {
+                    let mut digger_x = rng.roll_dice(1, self.map.width - 3) + 1;
+                    let mut digger_y = rng.roll_dice(1, self.map.height - 3) + 1;
+                    let mut prev_x = digger_x;
+                    let mut prev_y = digger_y;
+                    let mut digger_idx = self.map.xy_idx(digger_x, digger_y);
+
+                    let mut path = rltk::line2d(
+                        rltk::LineAlg::Bresenham,
+                        rltk::Point::new(digger_x, digger_y),
+                        rltk::Point::new(self.starting_position.x, self.starting_position.y)
+                    );
+
+                    while self.map.tiles[digger_idx] == TileType::Wall && !path.is_empty()
+                    {
+                        prev_x = digger_x;
+                        prev_y = digger_y;
+                        digger_x = path[0].x;
+                        digger_y = path[0].y;
+                        path.remove(0);
+                        digger_idx = self.map.xy_idx(digger_x, digger_y);
+                    }
+                    floor_tile_count += self.paint(prev_x, prev_y);
+                }
+            }
+        }
+
+        // Cull the areas we can't reach, and put the stairs as far away as possible
+        let exit_tile = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_tile] = TileType::DownStairs;
+
+        // Spawn some entities, keeping them off the player's starting tile
+        let spawn_regions = generate_voronoi_spawn_regions(&self.map, rng);
+        for region in spawn_regions.iter()
+        {
+            let area : Vec<usize> = region.iter().filter(|idx| **idx != start_idx).copied().collect();
+            spawner::spawn_region(rng, &area, self.depth, &mut self.spawn_list);
+        }
+    }
+
+    // Moves a particle one step in a random direction, staying clear of the map edge
+    fn stumble(&self, x : &mut i32, y : &mut i32, rng : &mut RandomNumberGenerator)
+    {
+        match rng.roll_dice(1, 4)
+        {
+            1 => { if *x > 2 { *x -= 1; } }
+            2 => { if *x < self.map.width - 2 { *x += 1; } }
+            3 => { if *y > 2 { *y -= 1; } }
+            _ => { if *y < self.map.height - 2 { *y += 1; } }
+        }
+    }
+
+    // Carves the brush out around a point, returning how many new floor tiles that made
+    fn paint(&mut self, x : i32, y : i32) -> usize
+    {
+        let mut painted = 0;
+        let half_brush = self.brush_size / 2;
+        for brush_y in y - half_brush .. y - half_brush + self.brush_size
+        {
+            for brush_x in x - half_brush .. x - half_brush + self.brush_size
+            {
+                if brush_x > 1 && brush_x < self.map.width - 1 && brush_y > 1 && brush_y < self.map.height - 1
+                {
+                    let idx = self.map.xy_idx(brush_x, brush_y);
+                    if self.map.tiles[idx] != TileType::Floor
+                    {
+                        self.map.tiles[idx] = TileType::Floor;
+                        painted += 1;
+                    }
+                }
+            }
+        }
+        painted
+    }
+
+    fn count_floor_tiles(&self) -> usize
+    {
+        self.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count()
+    }
+}
@@ -0,0 +1,175 @@
+use super::{MapBuilder, Map, TileType, Position, spawner,
+            remove_unreachable_areas_returning_most_distant, generate_voronoi_spawn_regions};
+use rltk::RandomNumberGenerator;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DrunkSpawnMode { StartingPoint, Random }
+
+pub struct DrunkardSettings
+{
+    pub spawn_mode : DrunkSpawnMode,
+    pub drunken_lifetime : i32,
+    pub floor_percent : f32,
+}
+
+pub struct DrunkardsWalkBuilder
+{
+    map : Map,
+    starting_position : Position,
+    depth : i32,
+    spawn_list : Vec<(usize, String)>,
+    settings : DrunkardSettings,
+}
+
+impl MapBuilder for DrunkardsWalkBuilder
+{
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        self.build(rng);
+    }
+
+    fn get_map(&self) -> Map
+    {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position
+    {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>
+    {
+        &self.spawn_list
+    }
+}
+
+impl DrunkardsWalkBuilder
+{
+    pub fn new(new_depth : i32, width : i32, height : i32, settings : DrunkardSettings) -> DrunkardsWalkBuilder
+    {
+        DrunkardsWalkBuilder
+        {
+            map : Map::new(new_depth, width, height),
+            starting_position : Position{ x: 0, y: 0 },
+            depth : new_depth,
+            spawn_list : Vec::new(),
+            settings,
+        }
+    }
+
+    // Every digger starts in the middle and wanders for a long time, giving one big open cavern
+    pub fn open_area(new_depth : i32, width : i32, height : i32) -> DrunkardsWalkBuilder
+    {
+        DrunkardsWalkBuilder::new(new_depth, width, height, DrunkardSettings
+        {
+            spawn_mode : DrunkSpawnMode::StartingPoint,
+            drunken_lifetime : 400,
+            floor_percent : 0.5,
+        })
+    }
+
+    // Diggers start anywhere and live longer, carving large connected halls
+    pub fn open_halls(new_depth : i32, width : i32, height : i32) -> DrunkardsWalkBuilder
+    {
+        DrunkardsWalkBuilder::new(new_depth, width, height, DrunkardSettings
+        {
+            spawn_mode : DrunkSpawnMode::Random,
+            drunken_lifetime : 400,
+            floor_percent : 0.5,
+        })
+    }
+
+    // Lots of short-lived diggers starting anywhere, giving narrow twisting tunnels
+    pub fn winding_passages(new_depth : i32, width : i32, height : i32) -> DrunkardsWalkBuilder
+    {
+        DrunkardsWalkBuilder::new(new_depth, width, height, DrunkardSettings
+        {
+            spawn_mode : DrunkSpawnMode::Random,
+            drunken_lifetime : 100,
+            floor_percent : 0.4,
+        })
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        // Set a central starting point
+        self.starting_position = Position{ x: self.map.width / 2, y: self.map.height / 2 };
+        let start_idx = self.map.xy_idx(self.starting_position.x, self.starting_position.y);
+        self.map.tiles[start_idx] = TileType::Floor;
+
+        let total_tiles = self.map.width * self.map.height;
+        let desired_floor_tiles = (self.settings.floor_percent * total_tiles as f32) as usize;
+        let mut floor_tile_count = self.count_floor_tiles();
+        let mut digger_count = 0;
+
+        // Bigger maps need diggers that stagger further, or they never reach the edges
+        let lifetime = self.settings.drunken_lifetime * i32::max(1, total_tiles / (80 * 43));
+        while floor_tile_count < desired_floor_tiles
+        {
+            let mut drunk_x;
+            let mut drunk_y;
+            match self.settings.spawn_mode
+            {
+                DrunkSpawnMode::StartingPoint =>
+                {
+                    drunk_x = self.starting_position.x;
+                    drunk_y = self.starting_position.y;
+                }
+                DrunkSpawnMode::Random =>
+                {
+                    if digger_count == 0
+                    {
+                        drunk_x = self.starting_position.x;
+                        drunk_y = self.starting_position.y;
+                    }
+                    else
+                    {
+                        drunk_x = rng.roll_dice(1, self.map.width - 3) + 1;
+                        drunk_y = rng.roll_dice(1, self.map.height - 3) + 1;
+                    }
+                }
+            }
+
+            let mut drunk_life = lifetime;
+            while drunk_life > 0
+            {
+                let drunk_idx = self.map.xy_idx(drunk_x, drunk_y);
+                if self.map.tiles[drunk_idx] != TileType::Floor
+                {
+                    self.map.tiles[drunk_idx] = TileType::Floor;
+                    floor_tile_count += 1;
+                }
+
+                match rng.roll_dice(1, 4)
+                {
+                    1 => { if drunk_x > 2 { drunk_x -= 1; } }
+                    2 => { if drunk_x < self.map.width - 2 { drunk_x += 1; } }
+                    3 => { if drunk_y > 2 { drunk_y -= 1; } }
+                    _ => { if drunk_y < self.map.height - 2 { drunk_y += 1; } }
+                }
+
+                drunk_life -= 1;
+            }
+
+            digger_count += 1;
+        }
+
+        // Cull the areas we can't reach, and put the stairs as far away as possible
+        let exit_tile = remove_unreachable_areas_returning_most_distant(&mut self.map, start_idx);
+        self.map.tiles[exit_tile] = TileType::DownStairs;
+
+        // Spawn some entities, keeping them off the player's starting tile
+        let spawn_regions = generate_voronoi_spawn_regions(&self.map, rng);
+        for region in spawn_regions.iter()
+        {
+            let area : Vec<usize> = region.iter().filter(|idx| **idx != start_idx).copied().collect();
+            spawner::spawn_region(rng, &area, self.depth, &mut self.spawn_list);
+        }
+    }
+
+    fn count_floor_tiles(&self) -> usize
+    {
+        self.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count()
+    }
+}
@@ -0,0 +1,76 @@
+use super::{Map, Rect, TileType, Position, spawner};
+use specs::prelude::*;
+use rltk::RandomNumberGenerator;
+mod common;
+use common::*;
+mod simple_map;
+use simple_map::SimpleMapBuilder;
+mod bsp_dungeon;
+use bsp_dungeon::BspDungeonBuilder;
+mod cellular_automata;
+use cellular_automata::CellularAutomataBuilder;
+mod drunkard;
+use drunkard::DrunkardsWalkBuilder;
+mod dla;
+use dla::DLABuilder;
+mod prefab_builder;
+use prefab_builder::PrefabBuilder;
+
+const MAX_MAP_WIDTH : i32 = 200;
+const MAX_MAP_HEIGHT : i32 = 200;
+
+pub trait MapBuilder
+{
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Position;
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>;
+
+    fn spawn_entities(&mut self, ecs : &mut World)
+    {
+        for entity in self.get_spawn_list().iter()
+        {
+            spawner::spawn_entity(ecs, entity);
+        }
+    }
+}
+
+// Levels start at the size of the screen and grow as the player descends
+fn level_size(new_depth : i32) -> (i32, i32)
+{
+    let width = i32::min(MAX_MAP_WIDTH, 80 + (new_depth - 1) * 12);
+    let height = i32::min(MAX_MAP_HEIGHT, 43 + (new_depth - 1) * 12);
+    (width, height)
+}
+
+// Picks the map builder used for a given depth. This is the only place that
+// should decide which generation algorithm a level uses.
+pub fn random_builder(new_depth : i32, rng : &mut RandomNumberGenerator) -> Box<dyn MapBuilder>
+{
+    let (width, height) = level_size(new_depth);
+
+    // Organic, room-less layouts only start showing up once the player is a few levels down
+    let mut builder_count = 2;
+    if new_depth >= 3 { builder_count += 7; }
+
+    let builder : Box<dyn MapBuilder> = match rng.roll_dice(1, builder_count)
+    {
+        1 => Box::new(BspDungeonBuilder::new(new_depth, width, height)),
+        2 => Box::new(SimpleMapBuilder::new(new_depth, width, height)),
+        3 => Box::new(CellularAutomataBuilder::new(new_depth, width, height)),
+        4 => Box::new(DrunkardsWalkBuilder::open_area(new_depth, width, height)),
+        5 => Box::new(DrunkardsWalkBuilder::open_halls(new_depth, width, height)),
+        6 => Box::new(DrunkardsWalkBuilder::winding_passages(new_depth, width, height)),
+        7 => Box::new(DLABuilder::walk_inwards(new_depth, width, height)),
+        8 => Box::new(DLABuilder::walk_outwards(new_depth, width, height)),
+        _ => Box::new(DLABuilder::central_attractor(new_depth, width, height))
+    };
+
+    // Sometimes dress the level up with a few hand-made vaults
+    if rng.roll_dice(1, 3) == 1
+    {
+        return Box::new(PrefabBuilder::vaults(new_depth, builder));
+    }
+
+    builder
+}
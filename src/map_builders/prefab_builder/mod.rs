@@ -0,0 +1,183 @@
+use super::{MapBuilder, Map, TileType, Position};
+use rltk::RandomNumberGenerator;
+use std::collections::HashSet;
+mod prefab_rooms;
+use prefab_rooms::PrefabRoom;
+
+pub struct PrefabBuilder
+{
+    map : Map,
+    starting_position : Position,
+    depth : i32,
+    previous_builder : Box<dyn MapBuilder>,
+    spawn_list : Vec<(usize, String)>,
+}
+
+impl MapBuilder for PrefabBuilder
+{
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        self.build(rng);
+    }
+
+    fn get_map(&self) -> Map
+    {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position
+    {
+        self.starting_position.clone()
+    }
+
+    fn get_spawn_list(&self) -> &Vec<(usize, String)>
+    {
+        &self.spawn_list
+    }
+}
+
+impl PrefabBuilder
+{
+    // Builds a level with another builder, then stamps hand-made vaults into its open floor
+    pub fn vaults(new_depth : i32, previous_builder : Box<dyn MapBuilder>) -> PrefabBuilder
+    {
+        PrefabBuilder
+        {
+            map : previous_builder.get_map(),
+            starting_position : Position{ x: 0, y: 0 },
+            depth : new_depth,
+            previous_builder,
+            spawn_list : Vec::new(),
+        }
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        self.previous_builder.build_map(rng);
+        self.map = self.previous_builder.get_map();
+        self.starting_position = self.previous_builder.get_starting_position();
+        self.spawn_list = self.previous_builder.get_spawn_list().clone();
+
+        self.apply_room_vaults(rng);
+    }
+
+    fn apply_room_vaults(&mut self, rng : &mut RandomNumberGenerator)
+    {
+        let master_vault_list = [prefab_rooms::TREASURE_ROOM, prefab_rooms::GOBLIN_AMBUSH,
+                                 prefab_rooms::ORC_GUARD_POST, prefab_rooms::CHECKERBOARD];
+
+        // Filter the vault list down to ones that are applicable to the current depth
+        let mut possible_vaults : Vec<&PrefabRoom> = master_vault_list
+            .iter()
+            .filter(|v| self.depth >= v.first_depth && self.depth <= v.last_depth)
+            .collect();
+
+        if possible_vaults.is_empty() { return; }
+
+        let n_vaults = i32::min(rng.roll_dice(1, 3), possible_vaults.len() as i32);
+        let start_idx = self.map.xy_idx(self.starting_position.x, self.starting_position.y);
+        let mut used_tiles : HashSet<usize> = HashSet::new();
+
+        for _i in 0 .. n_vaults
+        {
+            let vault_index = if possible_vaults.len() == 1 { 0 } else { (rng.roll_dice(1, possible_vaults.len() as i32) - 1) as usize };
+            let vault = possible_vaults[vault_index];
+
+            // Find every spot where the vault would sit entirely on open floor, clear of the player's
+            // start and any vault already placed
+            let mut vault_positions : Vec<Position> = Vec::new();
+            for y in 1 .. self.map.height - vault.height as i32
+            {
+                for x in 1 .. self.map.width - vault.width as i32
+                {
+                    let mut possible = true;
+                    'footprint: for ty in 0 .. vault.height as i32
+                    {
+                        for tx in 0 .. vault.width as i32
+                        {
+                            let idx = self.map.xy_idx(x + tx, y + ty);
+                            if self.map.tiles[idx] != TileType::Floor || idx == start_idx || used_tiles.contains(&idx)
+                            {
+                                possible = false;
+                                break 'footprint;
+                            }
+                        }
+                    }
+
+                    if possible
+                    {
+                        vault_positions.push(Position{ x, y });
+                    }
+                }
+            }
+
+            if !vault_positions.is_empty()
+            {
+                let pos_idx = if vault_positions.len() == 1 { 0 } else { (rng.roll_dice(1, vault_positions.len() as i32) - 1) as usize };
+                let pos = &vault_positions[pos_idx];
+
+                // Anything the level already wanted to spawn inside the vault makes way for it
+                let width = self.map.width;
+                self.spawn_list.retain(|e|
+                {
+                    let x = e.0 as i32 % width;
+                    let y = e.0 as i32 / width;
+                    !(x >= pos.x && x < pos.x + vault.width as i32 && y >= pos.y && y < pos.y + vault.height as i32)
+                });
+
+                let template = read_template(vault);
+                for ty in 0 .. vault.height
+                {
+                    for tx in 0 .. vault.width
+                    {
+                        let idx = self.map.xy_idx(pos.x + tx as i32, pos.y + ty as i32);
+                        self.char_to_map(template[ty * vault.width + tx], idx);
+                        used_tiles.insert(idx);
+                    }
+                }
+            }
+
+            possible_vaults.remove(vault_index);
+            if possible_vaults.is_empty() { break; }
+        }
+    }
+
+    fn char_to_map(&mut self, ch : char, idx : usize)
+    {
+        match ch
+        {
+            ' ' => {}
+            '#' => self.map.tiles[idx] = TileType::Wall,
+            '.' => self.map.tiles[idx] = TileType::Floor,
+            '>' => self.map.tiles[idx] = TileType::DownStairs,
+            _ =>
+            {
+                self.map.tiles[idx] = TileType::Floor;
+                match ch
+                {
+                    'g' => self.spawn_list.push((idx, "Goblin".to_string())),
+                    'o' => self.spawn_list.push((idx, "Orc".to_string())),
+                    '!' => self.spawn_list.push((idx, "Health Potion".to_string())),
+                    '?' => self.spawn_list.push((idx, "Magic Missile Scroll".to_string())),
+                    '/' => self.spawn_list.push((idx, "Dagger".to_string())),
+                    '(' => self.spawn_list.push((idx, "Shield".to_string())),
+                    _ => rltk::console::log(format!("Unknown glyph loading vault: {}", ch))
+                }
+            }
+        }
+    }
+}
+
+// Flattens a template into a width * height grid, padding short lines with spaces
+fn read_template(vault : &PrefabRoom) -> Vec<char>
+{
+    let mut grid = vec![' '; vault.width * vault.height];
+    for (y, line) in vault.template.lines().skip(1).take(vault.height).enumerate()
+    {
+        for (x, ch) in line.chars().take(vault.width).enumerate()
+        {
+            grid[y * vault.width + x] = ch;
+        }
+    }
+    grid
+}
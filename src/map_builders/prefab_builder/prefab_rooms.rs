@@ -0,0 +1,84 @@
+// Hand-authored vaults that get stamped into generated levels. Templates use the same glyphs
+// the camera renders for tiles ('#' wall, '.' floor, '>' stairs), plus a glyph for each entity
+// that can be placed. A space leaves whatever the underlying level had on that tile.
+
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabRoom
+{
+    pub template : &'static str,
+    pub width : usize,
+    pub height : usize,
+    pub first_depth : i32,
+    pub last_depth : i32
+}
+
+pub const TREASURE_ROOM : PrefabRoom = PrefabRoom
+{
+    template : TREASURE_ROOM_MAP,
+    width : 7,
+    height : 7,
+    first_depth : 1,
+    last_depth : 100
+};
+
+const TREASURE_ROOM_MAP : &str = "
+       
+ ##.## 
+ #!.?# 
+ ..... 
+ #/.(# 
+ ##.## 
+       
+";
+
+pub const GOBLIN_AMBUSH : PrefabRoom = PrefabRoom
+{
+    template : GOBLIN_AMBUSH_MAP,
+    width : 5,
+    height : 5,
+    first_depth : 1,
+    last_depth : 100
+};
+
+const GOBLIN_AMBUSH_MAP : &str = "
+g   g
+ ... 
+ .!. 
+ ... 
+g   g
+";
+
+pub const ORC_GUARD_POST : PrefabRoom = PrefabRoom
+{
+    template : ORC_GUARD_POST_MAP,
+    width : 7,
+    height : 5,
+    first_depth : 3,
+    last_depth : 100
+};
+
+const ORC_GUARD_POST_MAP : &str = "
+       
+ #o.o# 
+ ..!.. 
+ #o.o# 
+       
+";
+
+pub const CHECKERBOARD : PrefabRoom = PrefabRoom
+{
+    template : CHECKERBOARD_MAP,
+    width : 6,
+    height : 6,
+    first_depth : 2,
+    last_depth : 100
+};
+
+const CHECKERBOARD_MAP : &str = "
+      
+ #g#. 
+ .#!# 
+ #?#. 
+ .#g# 
+      
+";
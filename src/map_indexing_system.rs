@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use super::{Map, Position, BlocksTile};
+use super::{Map, Position, BlocksTile, BlocksVisibility, Door};
 
 pub struct MapIndexingSystem {}
 
@@ -8,14 +8,24 @@ impl<'a> System<'a> for MapIndexingSystem
     type SystemData = ( WriteExpect<'a, Map>,
                         ReadStorage<'a, Position>,
                         ReadStorage<'a, BlocksTile>,
+                        ReadStorage<'a, BlocksVisibility>,
+                        ReadStorage<'a, Door>,
                         Entities<'a>, );
 
     fn run(&mut self, data : Self::SystemData)
     {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, view_blockers, doors, entities) = data;
 
         map.populate_blocked();
         map.clear_content_index();
+        for vb in map.view_blocked.iter_mut()
+        {
+            *vb = false;
+        }
+        for door in map.closed_door.iter_mut()
+        {
+            *door = false;
+        }
         for (entity, position) in (&entities, &position).join()
         {
             let idx = map.xy_idx(position.x, position.y);
@@ -27,6 +37,16 @@ impl<'a> System<'a> for MapIndexingSystem
                 map.blocked[idx] = true;
             }
 
+            if view_blockers.get(entity).is_some()
+            {
+                map.view_blocked[idx] = true;
+            }
+
+            if doors.get(entity).is_some_and(|door| !door.open)
+            {
+                map.closed_door[idx] = true;
+            }
+
             // Push the entity to the appropriate index slot.
             map.tile_content[idx].push(entity);
         }
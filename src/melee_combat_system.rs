@@ -1,6 +1,10 @@
 use specs::prelude::*;
-use super::{CombatStats, WantsToMelee, Name, SufferDamage, gamelog::GameLog, MeleePowerBonus,
-                DefenseBonus, Equipped };
+use rltk::RandomNumberGenerator;
+use super::{CombatStats, WantsToMelee, Name, SufferDamage, gamelog::GameLog, MeleeWeapon, NaturalAttack,
+                DefenseBonus, Equipped, EquipmentSlot, Attributes, Skills, Position, Alertness, AlertState,
+                Pursuing, StatusEffects, StatusKind, status_system::has_status,
+                gamesystem::{attr_bonus, armor_class, roll_attack, roll_damage, AttackResult, make_noise} };
+use rltk::Point;
 
 pub struct MeleeCombatSystem {}
 
@@ -9,34 +13,56 @@ impl<'a> System<'a> for MeleeCombatSystem
     #[allow(clippy::type_complexity)]
     type SystemData = ( Entities<'a>,
                         WriteExpect<'a, GameLog>,
+                        WriteExpect<'a, RandomNumberGenerator>,
                         WriteStorage<'a, WantsToMelee>,
                         ReadStorage<'a, Name>,
                         ReadStorage<'a, CombatStats>,
                         WriteStorage<'a, SufferDamage>,
-                        ReadStorage<'a, MeleePowerBonus>,
+                        ReadStorage<'a, MeleeWeapon>,
+                        ReadStorage<'a, NaturalAttack>,
                         ReadStorage<'a, DefenseBonus>,
                         ReadStorage<'a, Equipped>,
+                        ReadStorage<'a, Attributes>,
+                        ReadStorage<'a, Skills>,
+                        ReadStorage<'a, Position>,
+                        WriteStorage<'a, Alertness>,
+                        WriteStorage<'a, Pursuing>,
+                        ReadStorage<'a, StatusEffects>,
                         );
 
     fn run(&mut self, data : Self::SystemData)
     {
-        let ( entities, mut log, mut wants_to_melee, names, combat_stats, mut inflict_damage,
-                melee_power_bonuses, defense_bonuses, equipped ) = data;
-        
+        let ( entities, mut log, mut rng, mut wants_to_melee, names, combat_stats, mut inflict_damage,
+                melee_weapons, natural_attacks, defense_bonuses, equipped, attributes, skills, positions,
+                mut alertness, mut pursuing, statuses ) = data;
+
+        let mut noises : Vec<Point> = Vec::new();
         for (entity, wants_to_melee, name, stats) 
          in (&entities, &wants_to_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0
             {
-                let mut offensive_bonus = 0;
-                for (_item_entity, power_bonus, equipped_by) in (&entities, &melee_power_bonuses, &equipped).join()
+                // Hit with whatever is in the melee slot, or with fists and claws if it's empty
+                let mut weapon_hit_bonus = 0;
+                let mut damage_dice = (1, 1, 0);
+                if let Some(natural) = natural_attacks.get(entity)
+                {
+                    damage_dice = (natural.damage_n_dice, natural.damage_die_type, natural.damage_bonus);
+                }
+                for (weapon, equipped_by) in (&melee_weapons, &equipped).join()
                 {
-                    if equipped_by.owner == entity
+                    if equipped_by.owner == entity && equipped_by.slot == EquipmentSlot::Melee
                     {
-                        offensive_bonus += power_bonus.power;
+                        weapon_hit_bonus = weapon.hit_bonus;
+                        damage_dice = (weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus);
                     }
                 }
 
+                // Might makes hits both likelier and harder, and training adds to the odds
+                let might_bonus = attributes.get(entity).map_or(0, |a| attr_bonus(a.might));
+                let melee_skill = skills.get(entity).map_or(0, |s| s.melee);
+                let hit_bonus = might_bonus + melee_skill + weapon_hit_bonus;
+
                 let target_stats = combat_stats.get(wants_to_melee.target).unwrap();
                 if target_stats.hp > 0
                 {
@@ -50,21 +76,61 @@ impl<'a> System<'a> for MeleeCombatSystem
                             defensive_bonus += defense_bonus.defense;
                         }
                     }
+                    let quickness = attributes.get(wants_to_melee.target).map_or(10, |a| a.quickness);
+                    let defense_skill = skills.get(wants_to_melee.target).map_or(0, |s| s.defense);
+                    let armor_class = armor_class(quickness, defense_skill, defensive_bonus);
 
-                    let damage = i32::max(0, (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus));
+                    // Sleepers can't get out of the way at all
+                    let sleeping = alertness.get(wants_to_melee.target).is_some_and(|a| a.state == AlertState::Sleeping)
+                        || has_status(statuses.get(wants_to_melee.target), StatusKind::Asleep);
+                    let mut result = roll_attack(&mut rng, hit_bonus, armor_class);
+                    if sleeping && result != AttackResult::Critical
+                    {
+                        result = AttackResult::Hit;
+                    }
+
+                    if let Some(pos) = positions.get(entity)
+                    {
+                        noises.push(Point::new(pos.x, pos.y));
+                    }
+
+                    if sleeping
+                    {
+                        log.entries.push(format!("{} catches {} asleep!", &name.name, &target_name.name));
+                    }
 
-                    if damage == 0
+                    if result == AttackResult::Fumble
                     {
-                        log.entries.push(format!("{} is unable to hurt {}", &name.name, &target_name.name));
+                        log.entries.push(format!("{} fumbles the attack on {}.", &name.name, &target_name.name));
+                    }
+                    else if result == AttackResult::Hit || result == AttackResult::Critical
+                    {
+                        let critical = result == AttackResult::Critical;
+                        let (n_dice, die_type, bonus) = damage_dice;
+                        let damage = roll_damage(&mut rng, (n_dice, die_type, bonus + might_bonus), critical);
+
+                        if critical
+                        {
+                            log.entries.push(format!("{} lands a critical hit on {}, for {} hp!", &name.name, &target_name.name, damage));
+                        }
+                        else
+                        {
+                            log.entries.push(format!("{} hits {}, for {} hp.", &name.name, &target_name.name, damage));
+                        }
+                        SufferDamage::new_damage(&mut inflict_damage, wants_to_melee.target, damage, Some(entity));
                     }
                     else
                     {
-                        log.entries.push(format!("{} hits {}, for {} hp.", &name.name, &target_name.name, damage));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_to_melee.target, damage);
+                        log.entries.push(format!("{} misses {}.", &name.name, &target_name.name));
                     }
                 }
             }
         }
         wants_to_melee.clear();
+
+        for noise in noises.iter()
+        {
+            make_noise(*noise, &positions, &mut alertness, &mut pursuing, &entities);
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,8 @@
 use rltk::{VirtualKeyCode, Rltk, Point};
 use specs::prelude::*;
 use super::{ Position, Player, Viewshed, CombatStats, State, Map, RunState, WantsToMelee,
-             Item, gamelog::GameLog, WantsToPickupItem, TileType};
+             Item, gamelog::GameLog, WantsToPickupItem, TileType, Door, BlocksTile, BlocksVisibility,
+             Renderable, EntityMoved, RangedWeapon, Ammunition, InBackpack, Equipped, EquipmentSlot, open_door};
 use std::cmp::{min, max};
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) 
@@ -11,8 +12,13 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World)
     let mut viewsheds = ecs.write_storage::<Viewshed>();
     let entities = ecs.entities();
     let combat_stats = ecs.read_storage::<CombatStats>();
-    let map = ecs.fetch::<Map>();
+    let mut map = ecs.fetch_mut::<Map>();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
+    let mut blocks_movement = ecs.write_storage::<BlocksTile>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let mut entity_moved = ecs.write_storage::<EntityMoved>();
 
     for (entity, _player, pos, viewshed) in (&entities, &mut players, &mut positions, &mut viewsheds).join() 
     {
@@ -31,12 +37,23 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World)
                 wants_to_melee.insert(entity, WantsToMelee{ target: *potential_target }).expect("Add target failed");
                 return;
             }
+
+            // Bumping into a closed door opens it, which takes the move
+            if doors.get(*potential_target).is_some_and(|door| !door.open)
+            {
+                let door_entity = *potential_target;
+                open_door(door_entity, destination_idx, &mut map, &mut doors, &mut blocks_movement,
+                          &mut blocks_visibility, &mut renderables);
+                viewshed.dirty = true;
+                return;
+            }
         }
 
         if !map.blocked[destination_idx]
         {
-            pos.x = min(crate::MAPWIDTH as i32 - 1, max(0, pos.x + delta_x));
-            pos.y = min(crate::MAPHEIGHT as i32 - 1, max(0, pos.y + delta_y));
+            pos.x = min(map.width - 1, max(0, pos.x + delta_x));
+            pos.y = min(map.height - 1, max(0, pos.y + delta_y));
+            entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert marker");
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
@@ -63,6 +80,23 @@ pub fn try_next_level(ecs: &mut World) -> bool
     }
 }
 
+pub fn try_previous_level(ecs: &mut World) -> bool
+{
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    if map.tiles[player_idx] == TileType::UpStairs
+    {
+        true
+    }
+    else
+    {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.entries.push("There is no way up from here.".to_string());
+        false
+    }
+}
+
 fn get_item(ecs: &mut World)
 {
     let player_pos = ecs.fetch::<Point>();
@@ -93,6 +127,40 @@ fn get_item(ecs: &mut World)
     }
 }
 
+// Returns the range of the player's ranged weapon, as long as there is something to fire from it
+fn try_fire(ecs: &mut World) -> Option<i32>
+{
+    let player_entity = ecs.fetch::<Entity>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let weapon = (&ranged_weapons, &equipped).join()
+        .find(|(_weapon, equipped_by)| equipped_by.owner == *player_entity && equipped_by.slot == EquipmentSlot::Ranged)
+        .map(|(weapon, _equipped_by)| weapon);
+    let weapon = match weapon
+    {
+        Some(weapon) => weapon,
+        None =>
+        {
+            gamelog.entries.push("You have nothing to shoot with.".to_string());
+            return None;
+        }
+    };
+
+    let has_ammo = (&ammunition, &backpack).join()
+        .any(|(ammo, carried)| carried.owner == *player_entity && ammo.ammo_type == weapon.ammo);
+    if !has_ammo
+    {
+        gamelog.entries.push(format!("You are out of {}s.", weapon.ammo.to_lowercase()));
+        return None;
+    }
+
+    Some(weapon.range)
+}
+
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState
 {
     // Player movement
@@ -140,6 +208,17 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState
 
             VirtualKeyCode::R => return RunState::ShowRemoveItem,
 
+            VirtualKeyCode::E => return RunState::ShowEquipment,
+
+            VirtualKeyCode::F =>
+            {
+                if let Some(range) = try_fire(&mut gs.ecs)
+                {
+                    return RunState::ShowFiring{ range };
+                }
+                return RunState::AwaitingInput;
+            }
+
             // Level changes
             VirtualKeyCode::Period => 
             {
@@ -149,6 +228,14 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState
                 }
             }
 
+            VirtualKeyCode::Comma =>
+            {
+                if try_previous_level(&mut gs.ecs)
+                {
+                    return RunState::PreviousLevel;
+                }
+            }
+
             // Save and Quit
             VirtualKeyCode::F5 => 
             {
@@ -0,0 +1,185 @@
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use rltk::{RandomNumberGenerator, Point};
+use super::{CombatStats, WantsToShoot, Name, SufferDamage, gamelog::GameLog, RangedWeapon, Ammunition,
+            InBackpack, Position, Renderable, Item, DefenseBonus, Equipped, EquipmentSlot, Attributes,
+            Skills, Viewshed, Map, TileType, SerializeMe, Alertness, Pursuing,
+            gamesystem::{attr_bonus, armor_class, roll_attack, roll_damage, AttackResult, make_noise} };
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem
+{
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        ReadExpect<'a, Entity>,
+                        ReadExpect<'a, Map>,
+                        WriteExpect<'a, GameLog>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        Read<'a, LazyUpdate>,
+                        WriteStorage<'a, WantsToShoot>,
+                        ReadStorage<'a, Name>,
+                        ReadStorage<'a, CombatStats>,
+                        WriteStorage<'a, SufferDamage>,
+                        ReadStorage<'a, RangedWeapon>,
+                        WriteStorage<'a, Ammunition>,
+                        WriteStorage<'a, InBackpack>,
+                        WriteStorage<'a, Position>,
+                        ReadStorage<'a, Renderable>,
+                        ReadStorage<'a, DefenseBonus>,
+                        ReadStorage<'a, Equipped>,
+                        ReadStorage<'a, Attributes>,
+                        ReadStorage<'a, Skills>,
+                        ReadStorage<'a, Viewshed>,
+                        WriteStorage<'a, Alertness>,
+                        WriteStorage<'a, Pursuing>,
+                        );
+
+    fn run(&mut self, data : Self::SystemData)
+    {
+        let (entities, player_entity, map, mut log, mut rng, lazy, mut wants_shoot, names, combat_stats,
+             mut inflict_damage, ranged_weapons, mut ammunition, mut backpack, mut positions, renderables,
+             defense_bonuses, equipped, attributes, skills, viewsheds, mut alertness, mut pursuing) = data;
+
+        let mut noises : Vec<Point> = Vec::new();
+        for (shooter, wants_shoot, name) in (&entities, &wants_shoot, &names).join()
+        {
+            let is_player = shooter == *player_entity;
+
+            let weapon = (&ranged_weapons, &equipped).join()
+                .find(|(_weapon, equipped_by)| equipped_by.owner == shooter && equipped_by.slot == EquipmentSlot::Ranged)
+                .map(|(weapon, _equipped_by)| weapon);
+            let weapon = if let Some(weapon) = weapon { weapon } else { continue; };
+
+            let ammo = (&entities, &ammunition, &backpack).join()
+                .find(|(_entity, ammo, carried)| carried.owner == shooter && ammo.ammo_type == weapon.ammo)
+                .map(|(entity, _ammo, _carried)| entity);
+            let ammo = match ammo
+            {
+                Some(ammo) => ammo,
+                None =>
+                {
+                    if is_player { log.entries.push(format!("You are out of {}s.", weapon.ammo.to_lowercase())); }
+                    continue;
+                }
+            };
+
+            // Only what the shooter can actually see is in the line of fire
+            let shooter_pos = positions.get(shooter).map(|pos| Point::new(pos.x, pos.y));
+            let in_sight = viewsheds.get(shooter).is_some_and(|vs| vs.visible_tiles.contains(&wants_shoot.target));
+            let in_range = shooter_pos.is_some_and(|pos|
+                rltk::DistanceAlg::Pythagoras.distance2d(pos, wants_shoot.target) <= weapon.range as f32);
+            if !in_sight || !in_range
+            {
+                if is_player { log.entries.push("You don't have a clear shot at that.".to_string()); }
+                continue;
+            }
+
+            let target_idx = map.xy_idx(wants_shoot.target.x, wants_shoot.target.y);
+            let target = map.tile_content[target_idx].iter()
+                .find(|e| **e != shooter && combat_stats.get(**e).is_some_and(|stats| stats.hp > 0))
+                .copied();
+
+            // Whatever the shot hits, anything nearby hears it land
+            noises.push(wants_shoot.target);
+
+            // Anything that flies into a wall breaks against it
+            let mut lands_on_floor = map.tiles[target_idx] != TileType::Wall;
+            match target
+            {
+                None =>
+                {
+                    if is_player { log.entries.push(format!("Your {} hits nothing.", weapon.ammo.to_lowercase())); }
+                }
+                Some(target) =>
+                {
+                    let target_name = &names.get(target).unwrap().name;
+
+                    // Quickness aims a bow the way might swings a sword
+                    let hit_bonus = attributes.get(shooter).map_or(0, |a| attr_bonus(a.quickness)) + weapon.hit_bonus;
+
+                    let mut armor = 0;
+                    for (defense_bonus, equipped_by) in (&defense_bonuses, &equipped).join()
+                    {
+                        if equipped_by.owner == target { armor += defense_bonus.defense; }
+                    }
+                    let quickness = attributes.get(target).map_or(10, |a| a.quickness);
+                    let defense_skill = skills.get(target).map_or(0, |s| s.defense);
+                    let armor_class = armor_class(quickness, defense_skill, armor);
+
+                    let result = roll_attack(&mut rng, hit_bonus, armor_class);
+                    if result == AttackResult::Fumble
+                    {
+                        log.entries.push(format!("{} fumbles the shot at {}.", &name.name, target_name));
+                    }
+                    else if result == AttackResult::Hit || result == AttackResult::Critical
+                    {
+                        let critical = result == AttackResult::Critical;
+                        let damage = roll_damage(&mut rng, (weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus), critical);
+
+                        if critical
+                        {
+                            log.entries.push(format!("{} shoots {} with a critical hit, for {} hp!", &name.name, target_name, damage));
+                        }
+                        else
+                        {
+                            log.entries.push(format!("{} shoots {}, for {} hp.", &name.name, target_name, damage));
+                        }
+                        SufferDamage::new_damage(&mut inflict_damage, target, damage, Some(shooter));
+
+                        // Half the time whatever hit breaks
+                        lands_on_floor = rng.roll_dice(1, 2) == 1;
+                    }
+                    else
+                    {
+                        log.entries.push(format!("{} shoots at {}, and misses.", &name.name, target_name));
+                    }
+                }
+            }
+
+            // Spend the shot. The last one in the stack can simply move to where it landed;
+            // otherwise a new one is split off the stack.
+            let ammo_stack = ammunition.get_mut(ammo).unwrap();
+            if ammo_stack.quantity <= 1
+            {
+                backpack.remove(ammo);
+                if lands_on_floor
+                {
+                    positions.insert(ammo, Position{ x : wants_shoot.target.x, y : wants_shoot.target.y })
+                        .expect("Unable to insert position");
+                }
+                else
+                {
+                    entities.delete(ammo).expect("Unable to delete ammunition");
+                }
+            }
+            else
+            {
+                ammo_stack.quantity -= 1;
+                if lands_on_floor
+                {
+                    let mut eb = lazy.create_entity(&entities)
+                        .with(Position{ x : wants_shoot.target.x, y : wants_shoot.target.y })
+                        .with(Item{})
+                        .with(Ammunition{ ammo_type : ammo_stack.ammo_type.clone(), quantity : 1 });
+                    if let Some(ammo_name) = names.get(ammo)
+                    {
+                        eb = eb.with(ammo_name.clone());
+                    }
+                    if let Some(renderable) = renderables.get(ammo)
+                    {
+                        eb = eb.with(renderable.clone());
+                    }
+                    eb.marked::<SimpleMarker<SerializeMe>>().build();
+                }
+            }
+        }
+
+        wants_shoot.clear();
+
+        for noise in noises.iter()
+        {
+            make_noise(*noise, &positions, &mut alertness, &mut pursuing, &entities);
+        }
+    }
+}
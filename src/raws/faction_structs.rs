@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// How one faction feels about the others. "Default" covers anyone not listed by name.
+#[derive(Deserialize, Debug)]
+pub struct FactionInfo
+{
+    pub name : String,
+    pub responses : HashMap<String, String>,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Reaction
+{
+    Ignore,
+    Attack,
+    Flee,
+}
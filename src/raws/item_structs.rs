@@ -0,0 +1,49 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct Item
+{
+    pub name : String,
+    pub renderable : Option<Renderable>,
+    pub consumable : Option<Consumable>,
+    pub equippable : Option<Equippable>,
+    pub ammunition : Option<Ammunition>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Renderable
+{
+    pub glyph : String,
+    pub fg : String,
+    pub bg : String,
+    pub order : i32,
+}
+
+// Each effect is a name ("provides_healing", "ranged", "damage", "area_of_effect", "food", or a
+// status effect such as "confusion" or "poison") and its amount
+#[derive(Deserialize, Debug)]
+pub struct Consumable
+{
+    pub effects : HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Equippable
+{
+    pub slot : String,
+    pub damage : Option<String>,
+    pub hit_bonus : Option<i32>,
+    pub defense_bonus : Option<i32>,
+    pub speed_bonus : Option<i32>,
+    pub range : Option<i32>,
+    pub ammo : Option<String>,
+    pub two_handed : Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Ammunition
+{
+    pub ammo_type : String,
+    pub quantity : i32,
+}
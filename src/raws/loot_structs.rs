@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+// What a monster might leave behind. A drop named "None" is the chance of leaving nothing.
+#[derive(Deserialize, Debug)]
+pub struct LootTable
+{
+    pub name : String,
+    pub drops : Vec<LootDrop>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LootDrop
+{
+    pub name : String,
+    pub weight : i32,
+}
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use super::Renderable;
+
+#[derive(Deserialize, Debug)]
+pub struct Mob
+{
+    pub name : String,
+    pub renderable : Option<Renderable>,
+    pub blocks_tile : bool,
+    pub attributes : MobAttributes,
+    pub skills : Option<HashMap<String, i32>>,
+    pub level : Option<i32>,
+    pub damage : String,
+    pub vision_range : i32,
+    pub xp_value : Option<i32>,
+    pub speed : Option<i32>,
+    pub loot_table : Option<String>,
+    pub faction : String,
+    pub abilities : Option<Vec<MobAbility>>,
+    pub sleep_chance : Option<i32>,
+}
+
+// Chance is the percentage chance of using the ability on a turn when it could be used
+#[derive(Deserialize, Debug)]
+pub struct MobAbility
+{
+    pub name : String,
+    pub cooldown : i32,
+    pub chance : i32,
+    pub effects : HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MobAttributes
+{
+    pub might : i32,
+    pub fitness : i32,
+    pub quickness : i32,
+    pub intelligence : i32,
+}
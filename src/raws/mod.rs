@@ -0,0 +1,44 @@
+mod item_structs;
+use item_structs::*;
+mod mob_structs;
+use mob_structs::*;
+mod prop_structs;
+use prop_structs::*;
+mod spawn_table_structs;
+use spawn_table_structs::*;
+mod loot_structs;
+use loot_structs::*;
+mod faction_structs;
+use faction_structs::*;
+pub use faction_structs::Reaction;
+mod rawmaster;
+pub use rawmaster::*;
+use serde::Deserialize;
+use std::sync::{LazyLock, Mutex};
+
+// Where the entity definitions live. They are read when the game starts, so they can be
+// rebalanced without recompiling.
+const RAW_FILE : &str = "resources/spawns.json";
+
+pub static RAWS : LazyLock<Mutex<RawMaster>> = LazyLock::new(|| Mutex::new(RawMaster::empty()));
+
+#[derive(Deserialize, Debug)]
+pub struct Raws
+{
+    pub items : Vec<Item>,
+    pub mobs : Vec<Mob>,
+    pub props : Vec<Prop>,
+    pub spawn_table : Vec<SpawnTableEntry>,
+    pub loot_tables : Vec<LootTable>,
+    pub faction_table : Vec<FactionInfo>,
+}
+
+pub fn load_raws()
+{
+    let raw_string = std::fs::read_to_string(RAW_FILE)
+        .unwrap_or_else(|e| panic!("Unable to read {}: {}", RAW_FILE, e));
+    let decoder : Raws = serde_json::from_str(&raw_string)
+        .unwrap_or_else(|e| panic!("Unable to parse {}: {}", RAW_FILE, e));
+
+    RAWS.lock().unwrap().load(decoder);
+}
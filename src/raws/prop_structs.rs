@@ -0,0 +1,23 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use super::Renderable;
+
+#[derive(Deserialize, Debug)]
+pub struct Prop
+{
+    pub name : String,
+    pub renderable : Option<Renderable>,
+    pub hidden : Option<bool>,
+    pub blocks_tile : Option<bool>,
+    pub blocks_visibility : Option<bool>,
+    pub door_open : Option<bool>,
+    pub entry_trigger : Option<EntryTrigger>,
+}
+
+// Each effect is a name ("damage", "teleport", "single_activation", or a status effect such as
+// "poison") and its amount
+#[derive(Deserialize, Debug)]
+pub struct EntryTrigger
+{
+    pub effects : HashMap<String, String>,
+}
@@ -0,0 +1,505 @@
+use std::collections::{HashMap, HashSet};
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+use crate::components::*;
+use crate::random_table::RandomTable;
+use crate::initiative_system::NORMAL_SPEED;
+use crate::gamesystem::max_hp_at_level;
+use super::{Raws, Reaction};
+
+// Percentage chance of a monster being asleep when it spawns, unless the raws say otherwise
+const DEFAULT_SLEEP_CHANCE : i32 = 40;
+
+pub enum SpawnType
+{
+    AtPosition { x : i32, y : i32 }
+}
+
+pub struct RawMaster
+{
+    raws : Raws,
+    item_index : HashMap<String, usize>,
+    mob_index : HashMap<String, usize>,
+    prop_index : HashMap<String, usize>,
+    loot_index : HashMap<String, usize>,
+    faction_index : HashMap<String, HashMap<String, Reaction>>,
+}
+
+impl RawMaster
+{
+    pub fn empty() -> RawMaster
+    {
+        RawMaster
+        {
+            raws : Raws{ items : Vec::new(), mobs : Vec::new(), props : Vec::new(), spawn_table : Vec::new(),
+                         loot_tables : Vec::new(), faction_table : Vec::new() },
+            item_index : HashMap::new(),
+            mob_index : HashMap::new(),
+            prop_index : HashMap::new(),
+            loot_index : HashMap::new(),
+            faction_index : HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, raws : Raws)
+    {
+        self.raws = raws;
+        self.item_index = HashMap::new();
+        self.mob_index = HashMap::new();
+        self.prop_index = HashMap::new();
+        self.loot_index = HashMap::new();
+        self.faction_index = HashMap::new();
+
+        // Names are what the spawn table and map builders refer to, so they have to be unique
+        let mut used_names : HashSet<String> = HashSet::new();
+        for (i, item) in self.raws.items.iter().enumerate()
+        {
+            if !used_names.insert(item.name.clone())
+            {
+                rltk::console::log(format!("WARNING - duplicate entity name in raws [{}]", item.name));
+            }
+            self.item_index.insert(item.name.clone(), i);
+        }
+        for (i, mob) in self.raws.mobs.iter().enumerate()
+        {
+            if !used_names.insert(mob.name.clone())
+            {
+                rltk::console::log(format!("WARNING - duplicate entity name in raws [{}]", mob.name));
+            }
+            self.mob_index.insert(mob.name.clone(), i);
+        }
+        for (i, prop) in self.raws.props.iter().enumerate()
+        {
+            if !used_names.insert(prop.name.clone())
+            {
+                rltk::console::log(format!("WARNING - duplicate entity name in raws [{}]", prop.name));
+            }
+            self.prop_index.insert(prop.name.clone(), i);
+        }
+
+        for spawn in self.raws.spawn_table.iter()
+        {
+            if !used_names.contains(&spawn.name)
+            {
+                rltk::console::log(format!("WARNING - spawn table references unspecified entity [{}]", spawn.name));
+            }
+        }
+
+        for (i, loot) in self.raws.loot_tables.iter().enumerate()
+        {
+            for drop in loot.drops.iter()
+            {
+                if drop.name != "None" && !self.item_index.contains_key(&drop.name)
+                {
+                    rltk::console::log(format!("WARNING - loot table {} references unspecified item [{}]", loot.name, drop.name));
+                }
+            }
+            self.loot_index.insert(loot.name.clone(), i);
+        }
+        for mob in self.raws.mobs.iter()
+        {
+            if let Some(table) = &mob.loot_table
+            {
+                if !self.loot_index.contains_key(table)
+                {
+                    rltk::console::log(format!("WARNING - {} uses unspecified loot table [{}]", mob.name, table));
+                }
+            }
+        }
+
+        for faction in self.raws.faction_table.iter()
+        {
+            let mut reactions : HashMap<String, Reaction> = HashMap::new();
+            for (other, response) in faction.responses.iter()
+            {
+                let reaction = match response.as_str()
+                {
+                    "ignore" => Reaction::Ignore,
+                    "attack" => Reaction::Attack,
+                    "flee" => Reaction::Flee,
+                    _ =>
+                    {
+                        rltk::console::log(format!("WARNING - faction {} has unknown response [{}]", faction.name, response));
+                        Reaction::Ignore
+                    }
+                };
+                reactions.insert(other.clone(), reaction);
+            }
+            self.faction_index.insert(faction.name.clone(), reactions);
+        }
+        for mob in self.raws.mobs.iter()
+        {
+            if !self.faction_index.contains_key(&mob.faction)
+            {
+                rltk::console::log(format!("WARNING - {} belongs to unspecified faction [{}]", mob.name, mob.faction));
+            }
+        }
+    }
+}
+
+fn spawn_position(pos : SpawnType, new_entity : EntityBuilder) -> EntityBuilder
+{
+    match pos
+    {
+        SpawnType::AtPosition{ x, y } => new_entity.with(Position{ x, y })
+    }
+}
+
+fn get_renderable_component(renderable : &super::item_structs::Renderable) -> Renderable
+{
+    Renderable
+    {
+        glyph : rltk::to_cp437(renderable.glyph.chars().next().unwrap()),
+        fg : rltk::RGB::from_hex(&renderable.fg).expect("Invalid RGB"),
+        bg : rltk::RGB::from_hex(&renderable.bg).expect("Invalid RGB"),
+        render_order : renderable.order,
+    }
+}
+
+fn parse_dice(dice : &str) -> rltk::DiceType
+{
+    rltk::parse_dice_string(dice).unwrap_or_else(|_| panic!("Invalid dice [{}]", dice))
+}
+
+// Status effects come in from the raws as a name and a number of turns. How hard each one
+// hits per turn is fixed by its kind.
+fn parse_status(effect_name : &str, amount : &str) -> Option<StatusEffect>
+{
+    let (kind, magnitude) = match effect_name
+    {
+        "confusion" => (StatusKind::Confused, 0),
+        "poison" => (StatusKind::Poisoned, 1),
+        "burning" => (StatusKind::Burning, 2),
+        "sleep" => (StatusKind::Asleep, 0),
+        "slow" => (StatusKind::Slowed, 0),
+        "regeneration" => (StatusKind::Regenerating, 2),
+        _ => return None
+    };
+    Some(StatusEffect{ kind, turns : parse_amount(effect_name, amount), magnitude })
+}
+
+fn parse_amount(effect_name : &str, amount : &str) -> i32
+{
+    amount.parse::<i32>().unwrap_or_else(|_| panic!("Invalid amount [{}] for effect {}", amount, effect_name))
+}
+
+// Items and monster abilities share one set of effects, so both are read the same way
+fn add_effect_components<'a>(mut eb : EntityBuilder<'a>, effects : &HashMap<String, String>) -> EntityBuilder<'a>
+{
+    let mut statuses : Vec<StatusEffect> = Vec::new();
+    for (effect_name, amount) in effects.iter()
+    {
+        if let Some(status) = parse_status(effect_name, amount)
+        {
+            statuses.push(status);
+            continue;
+        }
+        match effect_name.as_str()
+        {
+            "provides_healing" => { eb = eb.with(ProvidesHealing{ heal_amount : parse_amount(effect_name, amount) }) }
+            "ranged" => { eb = eb.with(Ranged{ range : parse_amount(effect_name, amount) }) }
+            "damage" => { eb = eb.with(InflictsDamage{ damage : parse_amount(effect_name, amount) }) }
+            "area_of_effect" => { eb = eb.with(AreaOfEffect{ radius : parse_amount(effect_name, amount) }) }
+            "food" => { eb = eb.with(ProvidesFood{}) }
+            _ => rltk::console::log(format!("Warning: effect {} not implemented.", effect_name))
+        }
+    }
+    if !statuses.is_empty()
+    {
+        eb = eb.with(InflictsStatus{ effects : statuses });
+    }
+    eb
+}
+
+// Builds an entity from its definition in the raws. Returns None if nothing has that name.
+pub fn spawn_named_entity(raws : &RawMaster, ecs : &mut World, key : &str, pos : SpawnType) -> Option<Entity>
+{
+    if raws.item_index.contains_key(key)
+    {
+        return spawn_named_item(raws, ecs, key, pos);
+    }
+    else if raws.mob_index.contains_key(key)
+    {
+        return spawn_named_mob(raws, ecs, key, pos);
+    }
+    else if raws.prop_index.contains_key(key)
+    {
+        return spawn_named_prop(raws, ecs, key, pos);
+    }
+
+    None
+}
+
+fn spawn_named_item(raws : &RawMaster, ecs : &mut World, key : &str, pos : SpawnType) -> Option<Entity>
+{
+    let item_template = &raws.raws.items[raws.item_index[key]];
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
+
+    eb = spawn_position(pos, eb);
+    if let Some(renderable) = &item_template.renderable
+    {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    eb = eb.with(Name{ name : item_template.name.clone() });
+    eb = eb.with(Item{});
+
+    if let Some(consumable) = &item_template.consumable
+    {
+        eb = eb.with(Consumable{});
+        eb = add_effect_components(eb, &consumable.effects);
+    }
+
+    if let Some(ammunition) = &item_template.ammunition
+    {
+        eb = eb.with(Ammunition{ ammo_type : ammunition.ammo_type.clone(), quantity : ammunition.quantity });
+    }
+
+    if let Some(equippable) = &item_template.equippable
+    {
+        let slot = match equippable.slot.as_str()
+        {
+            "Melee" => EquipmentSlot::Melee,
+            "Shield" => EquipmentSlot::Shield,
+            "Ranged" => EquipmentSlot::Ranged,
+            "Head" => EquipmentSlot::Head,
+            "Torso" => EquipmentSlot::Torso,
+            "Legs" => EquipmentSlot::Legs,
+            "Feet" => EquipmentSlot::Feet,
+            "Hands" => EquipmentSlot::Hands,
+            "Amulet" => EquipmentSlot::Amulet,
+            "Ring" => EquipmentSlot::Ring,
+            _ =>
+            {
+                rltk::console::log(format!("Warning: unknown equipment slot {} on {}.", equippable.slot, item_template.name));
+                EquipmentSlot::Melee
+            }
+        };
+        eb = eb.with(Equippable{ slot });
+        if let Some(damage) = &equippable.damage
+        {
+            let dice = parse_dice(damage);
+            if slot == EquipmentSlot::Ranged
+            {
+                eb = eb.with(RangedWeapon{
+                    range : equippable.range.unwrap_or_else(|| panic!("Ranged weapon {} has no range", item_template.name)),
+                    hit_bonus : equippable.hit_bonus.unwrap_or(0),
+                    damage_n_dice : dice.n_dice,
+                    damage_die_type : dice.die_type,
+                    damage_bonus : dice.bonus,
+                    ammo : equippable.ammo.clone().unwrap_or_else(|| panic!("Ranged weapon {} has no ammo", item_template.name)),
+                });
+            }
+            else
+            {
+                eb = eb.with(MeleeWeapon{
+                    hit_bonus : equippable.hit_bonus.unwrap_or(0),
+                    damage_n_dice : dice.n_dice,
+                    damage_die_type : dice.die_type,
+                    damage_bonus : dice.bonus,
+                });
+            }
+        }
+        if equippable.two_handed == Some(true)
+        {
+            eb = eb.with(TwoHanded{});
+        }
+        if let Some(defense) = equippable.defense_bonus
+        {
+            eb = eb.with(DefenseBonus{ defense });
+        }
+        if let Some(speed) = equippable.speed_bonus
+        {
+            eb = eb.with(SpeedBonus{ speed });
+        }
+    }
+
+    Some(eb.build())
+}
+
+fn spawn_named_mob(raws : &RawMaster, ecs : &mut World, key : &str, pos : SpawnType) -> Option<Entity>
+{
+    let mob_template = &raws.raws.mobs[raws.mob_index[key]];
+
+    // Some monsters are found asleep; the rest are up and about, but not yet looking for trouble
+    let sleeping = ecs.write_resource::<rltk::RandomNumberGenerator>().roll_dice(1, 100)
+        <= mob_template.sleep_chance.unwrap_or(DEFAULT_SLEEP_CHANCE);
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
+
+    eb = spawn_position(pos, eb);
+    if let Some(renderable) = &mob_template.renderable
+    {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    eb = eb.with(Name{ name : mob_template.name.clone() });
+    eb = eb.with(Monster{});
+    if mob_template.blocks_tile
+    {
+        eb = eb.with(BlocksTile{});
+    }
+
+    let attributes = &mob_template.attributes;
+    let max_hp = max_hp_at_level(attributes.fitness, mob_template.level.unwrap_or(1));
+    eb = eb.with(CombatStats{ max_hp, hp : max_hp });
+    eb = eb.with(Attributes{
+        might : attributes.might,
+        fitness : attributes.fitness,
+        quickness : attributes.quickness,
+        intelligence : attributes.intelligence,
+    });
+
+    // Anything the raws don't mention is untrained
+    let mut skills = Skills{ melee : 0, defense : 0, magic : 0 };
+    if let Some(mob_skills) = &mob_template.skills
+    {
+        for (skill_name, value) in mob_skills.iter()
+        {
+            match skill_name.as_str()
+            {
+                "melee" => skills.melee = *value,
+                "defense" => skills.defense = *value,
+                "magic" => skills.magic = *value,
+                _ => rltk::console::log(format!("Warning: unknown skill {} on {}.", skill_name, mob_template.name))
+            }
+        }
+    }
+    eb = eb.with(skills);
+
+    let damage = parse_dice(&mob_template.damage);
+    eb = eb.with(NaturalAttack{ damage_n_dice : damage.n_dice, damage_die_type : damage.die_type, damage_bonus : damage.bonus });
+    eb = eb.with(Viewshed{ visible_tiles : Vec::new(), range : mob_template.vision_range, dirty : true });
+    eb = eb.with(Initiative{ speed : mob_template.speed.unwrap_or(NORMAL_SPEED), energy : 0 });
+    if let Some(xp) = mob_template.xp_value
+    {
+        eb = eb.with(GivesExperience{ xp });
+    }
+    if let Some(table) = &mob_template.loot_table
+    {
+        eb = eb.with(LootTable{ table : table.clone() });
+    }
+    eb = eb.with(Faction{ name : mob_template.faction.clone() });
+    eb = eb.with(Alertness{ state : if sleeping { AlertState::Sleeping } else { AlertState::Wandering } });
+    let mob = eb.build();
+
+    if let Some(abilities) = &mob_template.abilities
+    {
+        for ability in abilities.iter()
+        {
+            spawn_ability(ecs, mob, ability);
+        }
+    }
+
+    Some(mob)
+}
+
+fn spawn_ability(ecs : &mut World, owner : Entity, ability : &super::mob_structs::MobAbility)
+{
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
+    eb = eb.with(Name{ name : ability.name.clone() });
+    eb = eb.with(Ability{ owner, cooldown : ability.cooldown, cooldown_left : 0, chance : ability.chance });
+
+    eb = add_effect_components(eb, &ability.effects);
+
+    eb.build();
+}
+
+fn spawn_named_prop(raws : &RawMaster, ecs : &mut World, key : &str, pos : SpawnType) -> Option<Entity>
+{
+    let prop_template = &raws.raws.props[raws.prop_index[key]];
+    let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
+
+    eb = spawn_position(pos, eb);
+    if let Some(renderable) = &prop_template.renderable
+    {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    eb = eb.with(Name{ name : prop_template.name.clone() });
+
+    if prop_template.hidden == Some(true)
+    {
+        eb = eb.with(Hidden{});
+    }
+    if prop_template.blocks_tile == Some(true)
+    {
+        eb = eb.with(BlocksTile{});
+    }
+    if prop_template.blocks_visibility == Some(true)
+    {
+        eb = eb.with(BlocksVisibility{});
+    }
+    if let Some(open) = prop_template.door_open
+    {
+        eb = eb.with(Door{ open });
+    }
+    if let Some(entry_trigger) = &prop_template.entry_trigger
+    {
+        eb = eb.with(EntryTrigger{});
+        let mut statuses : Vec<StatusEffect> = Vec::new();
+        for (effect_name, amount) in entry_trigger.effects.iter()
+        {
+            if let Some(status) = parse_status(effect_name, amount)
+            {
+                statuses.push(status);
+                continue;
+            }
+            match effect_name.as_str()
+            {
+                "damage" => { eb = eb.with(InflictsDamage{ damage : parse_amount(effect_name, amount) }) }
+                "teleport" => { eb = eb.with(Teleports{}) }
+                "single_activation" => { eb = eb.with(SingleActivation{}) }
+                _ => rltk::console::log(format!("Warning: entry trigger effect {} not implemented.", effect_name))
+            }
+        }
+        if !statuses.is_empty()
+        {
+            eb = eb.with(InflictsStatus{ effects : statuses });
+        }
+    }
+
+    Some(eb.build())
+}
+
+// Builds the weighted table of everything that can spawn at a given depth
+pub fn get_spawn_table_for_depth(raws : &RawMaster, depth : i32) -> RandomTable
+{
+    let mut rt = RandomTable::new();
+    for entry in raws.raws.spawn_table.iter()
+        .filter(|e| depth >= e.min_depth && depth <= e.max_depth)
+    {
+        let mut weight = entry.weight;
+        if entry.add_map_depth_to_weight == Some(true)
+        {
+            weight += depth;
+        }
+        rt = rt.add(entry.name.clone(), weight);
+    }
+
+    rt
+}
+
+// Rolls on a loot table, returning the name of the item dropped, if any
+pub fn get_item_drop(raws : &RawMaster, rng : &mut rltk::RandomNumberGenerator, table : &str) -> Option<String>
+{
+    let loot_table = &raws.raws.loot_tables[*raws.loot_index.get(table)?];
+    let mut rt = RandomTable::new();
+    for drop in loot_table.drops.iter()
+    {
+        rt = rt.add(drop.name.clone(), drop.weight);
+    }
+
+    let result = rt.roll(rng);
+    if result == "None" { None } else { Some(result) }
+}
+
+// How a member of one faction responds to seeing a member of another. Factions with no entry
+// for the other fall back on their "Default" response, and unknown factions ignore everyone.
+pub fn faction_reaction(my_faction : &str, their_faction : &str, raws : &RawMaster) -> Reaction
+{
+    match raws.faction_index.get(my_faction)
+    {
+        Some(reactions) => reactions.get(their_faction)
+            .or_else(|| reactions.get("Default"))
+            .copied()
+            .unwrap_or(Reaction::Ignore),
+        None => Reaction::Ignore
+    }
+}
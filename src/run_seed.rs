@@ -0,0 +1,120 @@
+use rltk::RandomNumberGenerator;
+use serde::{Serialize, Deserialize};
+
+// Every random stream in a run is derived from this one number, so a run (or a single level
+// of it) can be replayed exactly from its seed.
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+pub struct RunSeed
+{
+    pub seed : u64
+}
+
+// The independent streams a run draws from. Keeping them apart means that, for example, an
+// extra dice roll in a fight never changes the layout of the next level.
+#[derive(Copy, Clone)]
+pub enum RngStream
+{
+    // Level layout, and the monsters and items the level starts with
+    MapGeneration,
+    // Everything that happens at run time: combat, AI and anything spawned during play
+    World,
+}
+
+impl RunSeed
+{
+    pub fn new(seed : u64) -> RunSeed
+    {
+        RunSeed{ seed }
+    }
+
+    pub fn random() -> RunSeed
+    {
+        // Keep seeds short enough that people can comfortably type them back in
+        let mut rng = RandomNumberGenerator::new();
+        RunSeed{ seed : rng.range(1, 1_000_000) as u64 }
+    }
+
+    // Reads a seed from the command line, as either "--seed 12345" or "--seed=12345". A seed that
+    // can't be read stops the game, rather than quietly starting a different run.
+    pub fn from_args() -> Option<RunSeed>
+    {
+        let args : Vec<String> = std::env::args().collect();
+        for (i, arg) in args.iter().enumerate()
+        {
+            let value = if arg == "--seed"
+            {
+                args.get(i + 1).map(|s| s.as_str()).unwrap_or("")
+            }
+            else if let Some(value) = arg.strip_prefix("--seed=")
+            {
+                value
+            }
+            else
+            {
+                continue;
+            };
+
+            match value.parse::<u64>()
+            {
+                Ok(seed) => return Some(RunSeed::new(seed)),
+                Err(_) =>
+                {
+                    eprintln!("Invalid seed [{}]: expected a whole number, as in --seed 12345", value);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        None
+    }
+
+    // Creates the random number generator for one stream at one depth
+    pub fn rng_for(&self, stream : RngStream, depth : i32) -> RandomNumberGenerator
+    {
+        let stream_id = match stream
+        {
+            RngStream::MapGeneration => 1u64,
+            RngStream::World => 2u64,
+        };
+        let derived = mix(mix(self.seed ^ mix(stream_id)) ^ depth as u64);
+        RandomNumberGenerator::seeded(derived)
+    }
+}
+
+// The SplitMix64 finalizer: spreads nearby inputs (seed 1, seed 2, ...) into unrelated outputs
+fn mix(value : u64) -> u64
+{
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn rolls(mut rng : RandomNumberGenerator) -> Vec<i32>
+    {
+        (0..20).map(|_| rng.roll_dice(1, 1000)).collect()
+    }
+
+    #[test]
+    fn same_seed_stream_and_depth_repeat()
+    {
+        let seed = RunSeed::new(12345);
+        assert_eq!(rolls(seed.rng_for(RngStream::MapGeneration, 3)), rolls(seed.rng_for(RngStream::MapGeneration, 3)));
+        assert_eq!(rolls(seed.rng_for(RngStream::World, 3)), rolls(RunSeed::new(12345).rng_for(RngStream::World, 3)));
+    }
+
+    #[test]
+    fn streams_depths_and_seeds_diverge()
+    {
+        let seed = RunSeed::new(12345);
+        let base = rolls(seed.rng_for(RngStream::MapGeneration, 3));
+        assert_ne!(base, rolls(seed.rng_for(RngStream::World, 3)));
+        assert_ne!(base, rolls(seed.rng_for(RngStream::MapGeneration, 4)));
+        assert_ne!(base, rolls(RunSeed::new(12346).rng_for(RngStream::MapGeneration, 3)));
+    }
+}
@@ -24,9 +24,13 @@ pub fn save_game(ecs : &mut World)
 {
     // Create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let mut dungeon = (*ecs.fetch::<super::dungeon_master::DungeonMaster>()).clone();
+    dungeon.store_map(&mapcopy);
+    let seed = *ecs.fetch::<super::run_seed::RunSeed>();
+    let rng = (*ecs.fetch::<rltk::RandomNumberGenerator>()).clone();
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper{ map : mapcopy })
+        .with(SerializationHelper{ map : mapcopy, seed, rng, dungeon })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -37,10 +41,14 @@ pub fn save_game(ecs : &mut World)
         let writer = File::create("./savegame.json").unwrap();
         let mut serializer = serde_json::Serializer::new(writer);
         serialize_individually!(ecs, serializer, data, Position, Renderable, Player, Viewshed, Monster,
-            Name, BlocksTile, CombatStats, SufferDamage, WantsToMelee, Item, Consumable, Ranged, InflictsDamage,
-            AreaOfEffect, Confusion, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
+            Name, BlocksTile, CombatStats, WantsToMelee, Item, Consumable, Ranged, InflictsDamage,
+            AreaOfEffect, StatusEffects, InflictsStatus, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
             WantsToRemoveItem, 
-            SerializationHelper, Equippable, Equipped, MeleePowerBonus, DefenseBonus
+            SerializationHelper, Equippable, Equipped, MeleeWeapon, NaturalAttack, DefenseBonus, SpeedBonus, OtherLevelPosition,
+            BlocksVisibility, Door, Hidden, EntryTrigger, SingleActivation, Teleports, EntityMoved,
+            Experience, GivesExperience, HungerClock, ProvidesFood, Initiative, MyTurn,
+            Attributes, Skills, LootTable, RangedWeapon, Ammunition, WantsToShoot,
+            TwoHanded, Faction, Pursuing, Ability, Alertness
         );
     }
 
@@ -90,10 +98,14 @@ pub fn load_game(ecs: &mut World)
         let mut d = (&mut ecs.entities(), &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(), &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>());
         
         deserialize_individually!(ecs, de, d, Position, Renderable, Player, Viewshed, Monster, 
-            Name, BlocksTile, CombatStats, SufferDamage, WantsToMelee, Item, Consumable, Ranged, InflictsDamage, 
-            AreaOfEffect, Confusion, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
+            Name, BlocksTile, CombatStats, WantsToMelee, Item, Consumable, Ranged, InflictsDamage, 
+            AreaOfEffect, StatusEffects, InflictsStatus, ProvidesHealing, InBackpack, WantsToPickupItem, WantsToUseItem,
             WantsToRemoveItem, 
-            SerializationHelper, Equippable, Equipped, MeleePowerBonus, DefenseBonus
+            SerializationHelper, Equippable, Equipped, MeleeWeapon, NaturalAttack, DefenseBonus, SpeedBonus, OtherLevelPosition,
+            BlocksVisibility, Door, Hidden, EntryTrigger, SingleActivation, Teleports, EntityMoved,
+            Experience, GivesExperience, HungerClock, ProvidesFood, Initiative, MyTurn,
+            Attributes, Skills, LootTable, RangedWeapon, Ammunition, WantsToShoot,
+            TwoHanded, Faction, Pursuing, Ability, Alertness
         );
     }
 
@@ -107,7 +119,10 @@ pub fn load_game(ecs: &mut World)
         {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
-            worldmap.tile_content = vec![Vec::new(); super::map::MAPCOUNT];
+            worldmap.tile_content = vec![Vec::new(); (worldmap.width * worldmap.height) as usize];
+            *ecs.write_resource::<super::run_seed::RunSeed>() = h.seed;
+            *ecs.write_resource::<rltk::RandomNumberGenerator>() = h.rng.clone();
+            *ecs.write_resource::<super::dungeon_master::DungeonMaster>() = h.dungeon.clone();
             deleteme = Some(e);
         }
         for (e,_p,pos) in (&entities, &player, &position).join()
@@ -121,6 +136,7 @@ pub fn load_game(ecs: &mut World)
     ecs.delete_entity(deleteme.unwrap()).expect("Unable to delete helper");
 }
 
+#[allow(dead_code)]
 pub fn delete_save()
 {
     if Path::new("./savegame.json").exists() 
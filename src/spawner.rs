@@ -1,16 +1,18 @@
 use rltk::{ RGB, RandomNumberGenerator };
 use specs::prelude::*;
-use super::{CombatStats, Player, Renderable, Name, Position, Viewshed, 
-            Monster, BlocksTile, Rect, map::MAPWIDTH, Item, ProvidesHealing,
-            Consumable, InflictsDamage, Ranged, Confusion, AreaOfEffect, SerializeMe, 
-            random_table::RandomTable, Equippable, EquipmentSlot, MeleePowerBonus,
-            DefenseBonus };
+use super::{CombatStats, Player, Renderable, Name, Position, Viewshed, Rect, Map, TileType,
+            SerializeMe, random_table::RandomTable, Experience, HungerClock, HungerState,
+            hunger_system::WELL_FED_DURATION, Initiative, initiative_system::NORMAL_SPEED,
+            NaturalAttack, Attributes, Skills, gamesystem::max_hp_at_level, Faction};
+use super::raws::{RAWS, SpawnType, spawn_named_entity, get_spawn_table_for_depth};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
 
 // Spawns the player and returns his/her entity object.
 pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity
 {
+    let attributes = Attributes{ might: 14, fitness: 16, quickness: 12, intelligence: 11 };
+    let max_hp = max_hp_at_level(attributes.fitness, 1);
+
     ecs
         .create_entity()
         .with(Position { x: player_x, y: player_y })
@@ -23,7 +25,14 @@ pub fn player(ecs : &mut World, player_x : i32, player_y : i32) -> Entity
         .with(Player{})
         .with(Viewshed{ visible_tiles : Vec::new(), range: 8, dirty: true })
         .with(Name{ name: "Player".to_string() })
-        .with(CombatStats{ max_hp: 30, hp: 30, defense: 2, power: 5 })
+        .with(CombatStats{ max_hp, hp: max_hp })
+        .with(attributes)
+        .with(Skills{ melee: 2, defense: 1, magic: 1 })
+        .with(NaturalAttack{ damage_n_dice: 1, damage_die_type: 3, damage_bonus: 0 })
+        .with(Experience{ level: 1, xp: 0 })
+        .with(HungerClock{ state: HungerState::WellFed, duration: WELL_FED_DURATION })
+        .with(Initiative{ speed: NORMAL_SPEED, energy: 0 })
+        .with(Faction{ name: "Player".to_string() })
         .marked::<SimpleMarker<SerializeMe>>()
         .build()
 }
@@ -32,238 +41,65 @@ const MAX_MONSTERS : i32 = 4;
 
 fn room_table(map_depth: i32) -> RandomTable
 {
-    RandomTable::new()
-        .add("Goblin", 10)
-        .add("Orc", 1 + map_depth)
-        .add("Health Potion", 7)
-        .add("Fireball Scroll", 2 + map_depth)
-        .add("Confusion Scroll", 2 + map_depth)
-        .add("Magic Missile Scroll", 4)
-        .add("Dagger", 3)
-        .add("Longsword", map_depth - 1)
-        .add("Shield", 3)
-        .add("Tower Shield", map_depth - 1)
+    get_spawn_table_for_depth(&RAWS.lock().unwrap(), map_depth)
 }
 
-pub fn spawn_room(ecs: &mut World, room : &Rect, map_depth: i32)
+// Fills a room with stuff
+pub fn spawn_room(map : &Map, rng : &mut RandomNumberGenerator, room : &Rect, map_depth : i32, spawn_list : &mut Vec<(usize, String)>)
 {
-    let spawn_table = room_table(map_depth);
-    let mut spawn_points : HashMap<usize, String> = HashMap::new();
-
+    let mut possible_targets : Vec<usize> = Vec::new();
+    for y in room.y1 + 1 ..= room.y2
     {
-        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-        let num_spawns = rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3;
-
-        for _i in 0 .. num_spawns
+        for x in room.x1 + 1 ..= room.x2
         {
-            let mut added = false;
-            let mut tries = 0;
-            while !added && tries < 20
+            let idx = map.xy_idx(x, y);
+            if map.tiles[idx] == TileType::Floor
             {
-                let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
-                let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
-                let idx = (y * MAPWIDTH) + x;
-                if !spawn_points.contains_key(&idx) 
-                {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
-                    added = true;
-                }
-                else
-                {
-                    tries += 1;
-                }
+                possible_targets.push(idx);
             }
         }
     }
 
-    for spawn in spawn_points.iter()
-    {
-        let x = (*spawn.0 % MAPWIDTH) as i32;
-        let y = (*spawn.0 / MAPWIDTH) as i32;
-
-        match spawn.1.as_ref()
-        {
-            "Goblin" => goblin(ecs, x, y),
-            "Orc" => orc(ecs, x, y),
-            "Health Potion" => health_potion(ecs, x, y),
-            "Fireball Scroll" => fireball_scroll(ecs, x, y),
-            "Confusion Scroll" => confusion_scroll(ecs, x, y),
-            "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
-            "Dagger" => dagger(ecs, x, y),
-            "Longsword" => longsword(ecs, x, y),
-            "Shield" => shield(ecs, x, y),
-            "Tower Shield" => tower_shield(ecs, x, y),
-            _ => {}
-        }
-    }
+    spawn_region(rng, &possible_targets, map_depth, spawn_list);
 }
 
-fn orc(ecs: &mut World, x: i32, y: i32) { monster(ecs, x, y, rltk::to_cp437('o'), "Orc"); }
-fn goblin(ecs: &mut World, x: i32, y: i32) { monster(ecs, x, y, rltk::to_cp437('g'), "Goblin"); }
-
-fn monster<S :ToString>(ecs: &mut World, x: i32, y: i32, glyph : rltk::FontCharType, name : S)
+// Picks a depth-appropriate selection of monsters and items for tiles chosen from an
+// arbitrary list of map indices, so builders that have no rooms can still populate a level.
+pub fn spawn_region(rng : &mut RandomNumberGenerator, area : &[usize], map_depth : i32, spawn_list : &mut Vec<(usize, String)>)
 {
-     ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: glyph,
-            fg: RGB::named(rltk::RED),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 1,
-        })
-        .with(Viewshed{ visible_tiles : Vec::new(), range: 8, dirty: true })
-        .with(Monster{})
-        .with(Name{ name : name.to_string() })
-        .with(BlocksTile{})
-        .with(CombatStats{ max_hp: 16, hp: 16, defense: 1, power: 4 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
-
-fn health_potion(ecs: &mut World, x: i32, y: i32)
-{
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437('i'),
-            fg: RGB::named(rltk::MAGENTA),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name{ name : "Health Potion".to_string() })
-        .with(Item{})
-        .with(Consumable{})
-        .with(ProvidesHealing{ heal_amount: 8 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
-
-fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32)
-{
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name{ name : "Magic Missle Scroll".to_string() })
-        .with(Item{})
-        .with(Consumable{})
-        .with(InflictsDamage{ damage: 8 })
-        .with(Ranged{ range: 6 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
-
-fn fireball_scroll(ecs: &mut World, x: i32, y: i32)
-{
-     ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::ORANGE),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name{ name : "Fireball Scroll".to_string() })
-        .with(Item{})
-        .with(Consumable{})
-        .with(Ranged{ range: 6 })
-        .with(InflictsDamage{ damage: 8 })
-        .with(AreaOfEffect{ radius: 3 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
+    let spawn_table = room_table(map_depth);
+    let mut areas : Vec<usize> = Vec::from(area);
 
-fn confusion_scroll(ecs: &mut World, x: i32, y: i32)
-{
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::PINK),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2,
-        })
-        .with(Name{ name : "Confusion Scroll".to_string() })
-        .with(Item{})
-        .with(Consumable{})
-        .with(Ranged{ range: 6 })
-        .with(Confusion{ turns: 4 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
+    let num_spawns = i32::min(areas.len() as i32, rng.roll_dice(1, MAX_MONSTERS + 3) + (map_depth - 1) - 3);
 
-fn dagger(ecs: &mut World, x: i32, y: i32)
-{
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437('/'),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2
-        })
-        .with(Name{ name : "Dagger".to_string() })
-        .with(Item{})
-        .with(Equippable{ slot: EquipmentSlot::Melee })
-        .with(MeleePowerBonus{ power: 2 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+    // Each chosen tile is taken out of the pool, so nothing ever spawns on top of anything else
+    for _i in 0 .. num_spawns
+    {
+        let array_index = if areas.len() == 1 { 0usize } else { (rng.roll_dice(1, areas.len() as i32) - 1) as usize };
+        let map_idx = areas[array_index];
+        let roll = spawn_table.roll(rng);
+        if roll != "None"
+        {
+            spawn_list.push((map_idx, roll));
+        }
+        areas.remove(array_index);
+    }
 }
 
-fn longsword(ecs: &mut World, x: i32, y: i32)
+// Spawns a named entity at the given map index
+pub fn spawn_entity(ecs: &mut World, spawn : &(usize, String))
 {
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437('/'),
-            fg: RGB::named(rltk::YELLOW),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2
-        })
-        .with(Name{ name : "Longsword".to_string() })
-        .with(Item{})
-        .with(Equippable{ slot: EquipmentSlot::Melee })
-        .with(MeleePowerBonus{ power: 4 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
+    let x;
+    let y;
+    {
+        let map = ecs.fetch::<Map>();
+        x = (spawn.0 % map.width as usize) as i32;
+        y = (spawn.0 / map.width as usize) as i32;
+    }
 
-fn shield(ecs: &mut World, x: i32, y: i32)
-{
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437('('),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2
-        })
-        .with(Name{ name : "Shield".to_string() })
-        .with(Item{})
-        .with(Equippable{ slot: EquipmentSlot::Shield })
-        .with(DefenseBonus{ defense: 1 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
+    let spawn_result = spawn_named_entity(&RAWS.lock().unwrap(), ecs, &spawn.1, SpawnType::AtPosition{ x, y });
+    if spawn_result.is_none()
+    {
+        rltk::console::log(format!("WARNING: We don't know how to spawn [{}]!", spawn.1));
+    }
 }
-
-fn tower_shield(ecs: &mut World, x: i32, y: i32)
-{
-    ecs.create_entity()
-        .with(Position{ x, y })
-        .with(Renderable{
-            glyph: rltk::to_cp437('('),
-            fg: RGB::named(rltk::CYAN),
-            bg: RGB::named(rltk::BLACK),
-            render_order: 2
-        })
-        .with(Name{ name : "Tower Shield".to_string() })
-        .with(Item{})
-        .with(Equippable{ slot: EquipmentSlot::Shield })
-        .with(DefenseBonus{ defense: 3 })
-        .marked::<SimpleMarker<SerializeMe>>()
-        .build();
-}
\ No newline at end of file
@@ -0,0 +1,155 @@
+use specs::prelude::*;
+use super::{StatusEffects, StatusEffect, StatusKind, MyTurn, CombatStats, SufferDamage, RunState,
+            gamelog::GameLog};
+
+pub fn status_name(kind : StatusKind) -> &'static str
+{
+    match kind
+    {
+        StatusKind::Confused => "Confused",
+        StatusKind::Poisoned => "Poisoned",
+        StatusKind::Burning => "Burning",
+        StatusKind::Asleep => "Asleep",
+        StatusKind::Slowed => "Slowed",
+        StatusKind::Regenerating => "Regenerating",
+    }
+}
+
+// Effects that stop their owner from doing anything on their turn
+fn loses_turn(kind : StatusKind) -> bool
+{
+    kind == StatusKind::Confused || kind == StatusKind::Asleep
+}
+
+// Adds an effect to an entity. Getting the same effect again tops up its duration rather than
+// adding a second copy, except for poison, where the doses add up.
+pub fn apply_status(store : &mut WriteStorage<StatusEffects>, target : Entity, effect : &StatusEffect)
+{
+    if let Some(status) = store.get_mut(target)
+    {
+        if let Some(existing) = status.effects.iter_mut().find(|e| e.kind == effect.kind)
+        {
+            existing.turns = i32::max(existing.turns, effect.turns);
+            if effect.kind == StatusKind::Poisoned
+            {
+                existing.magnitude += effect.magnitude;
+            }
+            else
+            {
+                existing.magnitude = i32::max(existing.magnitude, effect.magnitude);
+            }
+        }
+        else
+        {
+            status.effects.push(effect.clone());
+        }
+    }
+    else
+    {
+        store.insert(target, StatusEffects{ effects : vec![effect.clone()] }).expect("Unable to insert status");
+    }
+}
+
+pub fn has_status(status : Option<&StatusEffects>, kind : StatusKind) -> bool
+{
+    status.is_some_and(|s| s.effects.iter().any(|e| e.kind == kind))
+}
+
+// True when the player can't act this turn, so input should be skipped
+pub fn player_loses_turn(ecs : &World) -> bool
+{
+    let player_entity = ecs.fetch::<Entity>();
+    let statuses = ecs.read_storage::<StatusEffects>();
+    statuses.get(*player_entity).is_some_and(|s| s.effects.iter().any(|e| loses_turn(e.kind)))
+}
+
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem
+{
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        ReadExpect<'a, Entity>,
+                        ReadExpect<'a, RunState>,
+                        WriteExpect<'a, GameLog>,
+                        WriteStorage<'a, StatusEffects>,
+                        WriteStorage<'a, MyTurn>,
+                        WriteStorage<'a, CombatStats>,
+                        WriteStorage<'a, SufferDamage>,
+                        );
+
+    fn run(&mut self, data : Self::SystemData)
+    {
+        let (entities, player_entity, runstate, mut log, mut statuses, mut turns, mut combat_stats,
+             mut inflict_damage) = data;
+
+        let mut expired : Vec<Entity> = Vec::new();
+        let mut lost_turns : Vec<Entity> = Vec::new();
+        for (entity, status) in (&entities, &mut statuses).join()
+        {
+            // Effects only tick on their owner's own turns
+            let is_player = entity == *player_entity;
+            let ticks = if is_player
+            {
+                *runstate == RunState::PlayerTurn
+            }
+            else
+            {
+                turns.get(entity).is_some()
+            };
+            if !ticks { continue; }
+
+            for effect in status.effects.iter_mut()
+            {
+                match effect.kind
+                {
+                    StatusKind::Poisoned | StatusKind::Burning =>
+                    {
+                        SufferDamage::new_damage(&mut inflict_damage, entity, effect.magnitude, None);
+                        if is_player
+                        {
+                            log.entries.push(format!("You are {}, and lose {} hp.", status_name(effect.kind).to_lowercase(), effect.magnitude));
+                        }
+                    }
+                    StatusKind::Regenerating =>
+                    {
+                        if let Some(stats) = combat_stats.get_mut(entity)
+                        {
+                            stats.hp = i32::min(stats.max_hp, stats.hp + effect.magnitude);
+                        }
+                    }
+                    StatusKind::Confused | StatusKind::Asleep =>
+                    {
+                        lost_turns.push(entity);
+                        if is_player
+                        {
+                            log.entries.push(format!("You are {}, and can't act.", status_name(effect.kind).to_lowercase()));
+                        }
+                    }
+                    StatusKind::Slowed => {}
+                }
+
+                effect.turns -= 1;
+                if effect.turns < 1 && is_player
+                {
+                    log.entries.push(format!("You are no longer {}.", status_name(effect.kind).to_lowercase()));
+                }
+            }
+
+            status.effects.retain(|e| e.turns > 0);
+            if status.effects.is_empty()
+            {
+                expired.push(entity);
+            }
+        }
+
+        for entity in expired.iter()
+        {
+            statuses.remove(*entity);
+        }
+        for entity in lost_turns.iter()
+        {
+            turns.remove(*entity);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use specs::prelude::*;
+use super::{EntityMoved, Position, EntryTrigger, Hidden, Map, Name, gamelog::GameLog,
+            InflictsDamage, SufferDamage, SingleActivation, Teleports, TileType, Viewshed, Player,
+            InflictsStatus, StatusEffects, status_system::apply_status};
+use rltk::{RandomNumberGenerator, Point};
+
+pub struct TriggerSystem {}
+
+impl<'a> System<'a> for TriggerSystem
+{
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( WriteExpect<'a, Map>,
+                        WriteExpect<'a, Point>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteExpect<'a, GameLog>,
+                        Entities<'a>,
+                        WriteStorage<'a, EntityMoved>,
+                        WriteStorage<'a, Position>,
+                        ReadStorage<'a, EntryTrigger>,
+                        WriteStorage<'a, Hidden>,
+                        ReadStorage<'a, Name>,
+                        ReadStorage<'a, InflictsDamage>,
+                        WriteStorage<'a, SufferDamage>,
+                        ReadStorage<'a, SingleActivation>,
+                        ReadStorage<'a, Teleports>,
+                        WriteStorage<'a, Viewshed>,
+                        ReadStorage<'a, Player>,
+                        ReadStorage<'a, InflictsStatus>,
+                        WriteStorage<'a, StatusEffects>,
+                        );
+
+    fn run(&mut self, data : Self::SystemData)
+    {
+        let (mut map, mut player_pos, mut rng, mut log, entities, mut entity_moved, mut positions,
+             entry_triggers, mut hidden, names, inflicts_damage, mut suffer_damage, single_activation,
+             teleports, mut viewsheds, players, inflicts_status, mut statuses) = data;
+
+        // Find out what everyone that moved this turn stepped on
+        let mut fired : Vec<(Entity, Entity)> = Vec::new();
+        for (entity, _moved, pos) in (&entities, &entity_moved, &positions).join()
+        {
+            let idx = map.xy_idx(pos.x, pos.y);
+            for trap in map.tile_content[idx].iter()
+            {
+                if entity != *trap && entry_triggers.get(*trap).is_some()
+                {
+                    fired.push((entity, *trap));
+                }
+            }
+        }
+        entity_moved.clear();
+
+        let mut remove_traps : Vec<Entity> = Vec::new();
+        for (victim, trap) in fired.iter()
+        {
+            let idx = {
+                let pos = positions.get(*victim).unwrap();
+                map.xy_idx(pos.x, pos.y)
+            };
+
+            // Whoever is watching now knows the trap is there
+            if map.visible_tiles[idx]
+            {
+                hidden.remove(*trap);
+                if let (Some(victim_name), Some(trap_name)) = (names.get(*victim), names.get(*trap))
+                {
+                    log.entries.push(format!("{} triggers a {}!", &victim_name.name, &trap_name.name));
+                }
+            }
+
+            if let Some(damage) = inflicts_damage.get(*trap)
+            {
+                SufferDamage::new_damage(&mut suffer_damage, *victim, damage.damage, None);
+            }
+
+            if let Some(inflicts) = inflicts_status.get(*trap)
+            {
+                for effect in inflicts.effects.iter()
+                {
+                    apply_status(&mut statuses, *victim, effect);
+                }
+            }
+
+            if teleports.get(*trap).is_some()
+            {
+                if let Some(destination) = random_open_tile(&map, &mut rng)
+                {
+                    // Keep the map up to date, so anyone else teleported this turn lands elsewhere
+                    map.blocked[idx] = false;
+                    let destination_idx = map.xy_idx(destination.x, destination.y);
+                    map.blocked[destination_idx] = true;
+
+                    let pos = positions.get_mut(*victim).unwrap();
+                    pos.x = destination.x;
+                    pos.y = destination.y;
+                    if players.get(*victim).is_some()
+                    {
+                        *player_pos = destination;
+                    }
+                    if let Some(viewshed) = viewsheds.get_mut(*victim)
+                    {
+                        viewshed.dirty = true;
+                    }
+                }
+            }
+
+            if single_activation.get(*trap).is_some()
+            {
+                remove_traps.push(*trap);
+            }
+        }
+
+        for trap in remove_traps.iter()
+        {
+            entities.delete(*trap).expect("Unable to delete trap");
+        }
+    }
+}
+
+fn random_open_tile(map : &Map, rng : &mut RandomNumberGenerator) -> Option<Point>
+{
+    let open_tiles : Vec<usize> = map.tiles.iter().enumerate()
+        .filter(|(idx, tile)| **tile == TileType::Floor && !map.blocked[*idx])
+        .map(|(idx, _tile)| idx)
+        .collect();
+
+    if open_tiles.is_empty() { return None; }
+
+    let idx = open_tiles[(rng.roll_dice(1, open_tiles.len() as i32) - 1) as usize];
+    Some(Point::new(idx as i32 % map.width, idx as i32 / map.width))
+}
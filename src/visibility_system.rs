@@ -1,20 +1,30 @@
 use specs::prelude::*;
-use super::{Viewshed, Position, Map, Player};
-use rltk::{field_of_view, Point};
+use super::{Viewshed, Position, Map, Player, Hidden, Name, RunState, gamelog::GameLog};
+use rltk::{field_of_view, Point, RandomNumberGenerator};
+
+// How close a hidden trap has to be for the player to have a chance of noticing it
+const SPOT_RANGE : f32 = 4.0;
 
 pub struct VisibilitySystem {}
 
 impl<'a> System<'a> for VisibilitySystem 
 {
+    #[allow(clippy::type_complexity)]
     type SystemData = ( WriteExpect<'a, Map>,
                         Entities<'a>,
                         WriteStorage<'a, Viewshed>,
                         WriteStorage<'a, Position>,
-                        ReadStorage<'a, Player>);
+                        ReadStorage<'a, Player>,
+                        ReadExpect<'a, RunState>,
+                        WriteExpect<'a, RandomNumberGenerator>,
+                        WriteStorage<'a, Hidden>,
+                        ReadStorage<'a, Name>,
+                        WriteExpect<'a, GameLog>);
 
     fn run(&mut self, data : Self::SystemData) 
     {
-        let (mut map, entities, mut viewshed, pos, player) = data;
+        let (mut map, entities, mut viewshed, pos, player, runstate, mut rng, mut hidden, names,
+             mut log) = data;
         
         for (ent, viewshed, pos) in (&entities, &mut viewshed, &pos).join() 
         {
@@ -42,5 +52,31 @@ impl<'a> System<'a> for VisibilitySystem
                 }
             }
         }
+
+        // Once a turn, the player gets a chance to spot each hidden thing close by
+        if *runstate == RunState::PlayerTurn
+        {
+            for (_player, viewshed, player_pos) in (&player, &viewshed, &pos).join()
+            {
+                let player_point = Point::new(player_pos.x, player_pos.y);
+                for vis in viewshed.visible_tiles.iter()
+                {
+                    if rltk::DistanceAlg::Pythagoras.distance2d(player_point, *vis) > SPOT_RANGE { continue; }
+
+                    let idx = map.xy_idx(vis.x, vis.y);
+                    for e in map.tile_content[idx].iter()
+                    {
+                        if hidden.get(*e).is_some() && rng.roll_dice(1, 24) == 1
+                        {
+                            if let Some(name) = names.get(*e)
+                            {
+                                log.entries.push(format!("You spotted a {}.", &name.name));
+                            }
+                            hidden.remove(*e);
+                        }
+                    }
+                }
+            }
+        }
     }
-}
\ No newline at end of file
+}